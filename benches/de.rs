@@ -0,0 +1,160 @@
+//! Benchmarks of the ways to deserialize rows, run against the database the
+//! tests use, e.g. `PGPASSWORD=postgres cargo bench`.
+extern crate criterion;
+extern crate postgres;
+extern crate serde_bytes;
+extern crate serde_derive;
+extern crate serde_tokio_postgres as serde_postgres;
+
+use std::env;
+
+use criterion::{black_box, criterion_group, criterion_main, BatchSize, Criterion};
+use postgres::{Client, NoTls};
+use serde_bytes::ByteBuf;
+use serde_derive::Deserialize;
+
+fn connect() -> Client {
+    let user = env::var("PGUSER").unwrap_or("postgres".into());
+    let pass = env::var("PGPASSWORD").unwrap_or("postgres".into());
+    let addr = env::var("PGADDR").unwrap_or("localhost".into());
+    let port = env::var("PGPORT").unwrap_or("5432".into());
+    let url = format!("postgres://{user}:{pass}@{addr}:{port}", user = user, pass = pass, addr = addr, port = port);
+    Client::connect(&url, NoTls).unwrap()
+}
+
+#[derive(Deserialize)]
+#[allow(dead_code)]
+struct Buu {
+    id: i32,
+    catchphrase: String,
+    width: i16,
+    weight: f64,
+}
+
+/// A thousand rows with `Buu`'s columns, in the order of its fields.
+const BUUS: &str = "SELECT id, 'Woo Woo ' || id AS catchphrase, 20::int2 AS width,
+    9999.9999::float8 AS weight FROM generate_series(1, 1000) AS id";
+
+/// `from_row_ordered` reads columns by position, `from_row` by name.
+fn ordered(c: &mut Criterion) {
+    let mut client = connect();
+    let mut group = c.benchmark_group("from_row_ordered");
+
+    group.bench_function("by name", |b| b.iter_batched(
+        || client.query(BUUS, &[]).unwrap(),
+        |rows| rows.into_iter()
+            .map(|row| serde_postgres::from_row::<Buu>(row).unwrap())
+            .collect::<Vec<_>>(),
+        BatchSize::LargeInput));
+
+    group.bench_function("ordered", |b| b.iter_batched(
+        || client.query(BUUS, &[]).unwrap(),
+        |rows| rows.into_iter()
+            .map(|row| serde_postgres::from_row_ordered::<Buu>(row).unwrap())
+            .collect::<Vec<_>>(),
+        BatchSize::LargeInput));
+
+    group.finish();
+}
+
+/// `CachedDeserializer` resolves the columns' keys once for every batch
+/// with the same columns, `from_rows` for every row.
+fn cached(c: &mut Criterion) {
+    let mut client = connect();
+    let mut group = c.benchmark_group("CachedDeserializer");
+
+    group.bench_function("from_rows", |b| b.iter_batched(
+        || client.query(BUUS, &[]).unwrap(),
+        |rows| serde_postgres::from_rows::<Buu>(rows).unwrap(),
+        BatchSize::LargeInput));
+
+    let mut cached = serde_postgres::CachedDeserializer::<Buu>::new();
+    group.bench_function("cached", |b| b.iter_batched(
+        || client.query(BUUS, &[]).unwrap(),
+        |rows| cached.from_rows(rows).unwrap(),
+        BatchSize::LargeInput));
+
+    group.finish();
+}
+
+#[derive(Deserialize)]
+struct Blob {
+    data: ByteBuf,
+}
+
+/// A thousand rows with a 4 KiB `bytea` each.
+const BLOBS: &str = "SELECT decode(repeat('ab', 4096), 'hex') AS data FROM generate_series(1, 1000)";
+
+/// `from_rows_with_buffer_pool` reads into the buffers of the previous
+/// iteration's values, `from_rows` allocates new ones.
+fn buffer_pool(c: &mut Criterion) {
+    let mut client = connect();
+    let mut group = c.benchmark_group("from_rows_with_buffer_pool");
+
+    group.bench_function("from_rows", |b| b.iter_batched(
+        || client.query(BLOBS, &[]).unwrap(),
+        |rows| serde_postgres::from_rows::<Blob>(rows).unwrap(),
+        BatchSize::LargeInput));
+
+    let mut pool = serde_postgres::BufferPool::new();
+    group.bench_function("pooled", |b| b.iter_batched(
+        || client.query(BLOBS, &[]).unwrap(),
+        |rows| {
+            let blobs: Vec<Blob> = serde_postgres::from_rows_with_buffer_pool(rows, &mut pool).unwrap();
+            for blob in blobs {
+                pool.put(blob.data.into_vec());
+            }
+        },
+        BatchSize::LargeInput));
+
+    group.finish();
+}
+
+/// `from_rows_reuse` resets one `Deserializer` for every row, `from_rows`
+/// creates a new one.
+fn reuse(c: &mut Criterion) {
+    let mut client = connect();
+    let mut group = c.benchmark_group("from_rows_reuse");
+
+    group.bench_function("from_rows", |b| b.iter_batched(
+        || client.query(BUUS, &[]).unwrap(),
+        |rows| serde_postgres::from_rows::<Buu>(rows).unwrap(),
+        BatchSize::LargeInput));
+
+    group.bench_function("reused", |b| b.iter_batched(
+        || client.query(BUUS, &[]).unwrap(),
+        |rows| serde_postgres::from_rows_reuse::<Buu>(rows).unwrap(),
+        BatchSize::LargeInput));
+
+    group.finish();
+}
+
+/// `column_index` scans every column of a 50-column row, or looks the name
+/// up in the index built by `with_column_index_cache`.
+fn column_index(c: &mut Criterion) {
+    let mut client = connect();
+    let columns: Vec<_> = (0..50).map(|i| format!("{} AS c{}", i, i)).collect();
+    let row = client.query_one(&*format!("SELECT {}", columns.join(", ")), &[]).unwrap();
+    let names: Vec<_> = (0..50).map(|i| format!("c{}", i)).collect();
+
+    let mut group = c.benchmark_group("column_index");
+
+    let linear = serde_postgres::Deserializer::from_row_ref(&row);
+    group.bench_function("linear", |b| b.iter(|| {
+        for name in &names {
+            black_box(linear.column_index(name));
+        }
+    }));
+
+    let cached = serde_postgres::Deserializer::from_row_ref(&row).with_column_index_cache();
+    group.bench_function("cached", |b| b.iter(|| {
+        for name in &names {
+            black_box(cached.column_index(name));
+        }
+    }));
+
+    group.finish();
+}
+
+criterion_group!(benches, ordered, cached, buffer_pool, reuse, column_index);
+criterion_main!(benches);
@@ -0,0 +1,332 @@
+//! Deserialize Postgres arrays element by element.
+use std::str;
+
+use serde::de::{self, DeserializeSeed, SeqAccess, Visitor};
+use tokio_postgres::types::{Kind, Type};
+
+use config::Config;
+use de::UnitVariant;
+use error::{Error, Result};
+use lsn::Lsn;
+use network::Network;
+use raw;
+#[cfg(feature = "with-chrono")]
+use timestamp;
+
+/// An array column split into the raw values of its elements, borrowed from
+/// the column's binary representation.
+///
+/// Postgres prefixes every element with its length, so the elements are
+/// never contiguous and an `int4[]` can't be borrowed as `&[i32]`. What can
+/// be borrowed is each element's big-endian bytes, e.g. read back with
+/// `i32::from_be_bytes`, without copying or allocating per element.
+pub struct RawArray<'a> {
+    dimensions: Vec<usize>,
+    elements: Vec<Option<&'a [u8]>>,
+}
+
+impl<'a> RawArray<'a> {
+    /// Parse the binary representation of an array, with the elements of
+    /// multi-dimensional arrays in row-major order.
+    pub fn from_sql(mut buf: &'a [u8]) -> Result<Self> {
+        let dimension_count = read_i32(&mut buf)?;
+        let _has_nulls = read_i32(&mut buf)?;
+        let _element_oid = read_i32(&mut buf)?;
+
+        let mut dimensions = Vec::with_capacity(dimension_count.max(0) as usize);
+        for _ in 0..dimension_count {
+            dimensions.push(read_i32(&mut buf)? as usize);
+            let _lower_bound = read_i32(&mut buf)?;
+        }
+        let count = if dimensions.is_empty() { 0 } else { dimensions.iter().product() };
+
+        let mut elements = Vec::with_capacity(count);
+        for _ in 0..count {
+            let len = read_i32(&mut buf)?;
+            if len < 0 {
+                elements.push(None);
+                continue;
+            }
+
+            let len = len as usize;
+            if buf.len() < len {
+                return Err(truncated());
+            }
+            let (element, rest) = buf.split_at(len);
+            elements.push(Some(element));
+            buf = rest;
+        }
+
+        Ok(RawArray { dimensions, elements })
+    }
+
+    /// The length of every dimension, outermost first, e.g. `[2, 3]` for
+    /// two rows of three elements. Empty for an empty array.
+    pub fn dimensions(&self) -> &[usize] {
+        &self.dimensions
+    }
+
+    /// The raw value of every element, `None` for `NULL`.
+    pub fn elements(&self) -> &[Option<&'a [u8]>] {
+        &self.elements
+    }
+}
+
+fn read_i32(buf: &mut &[u8]) -> Result<i32> {
+    if buf.len() < 4 {
+        return Err(truncated());
+    }
+    let (head, rest) = buf.split_at(4);
+    *buf = rest;
+    Ok(i32::from_be_bytes([head[0], head[1], head[2], head[3]]))
+}
+
+fn truncated() -> Error {
+    Error::InvalidType(String::from("array value is truncated"))
+}
+
+/// Whether `ty` is a Postgres enum type.
+fn is_enum(ty: &Type) -> bool {
+    match *ty.kind() {
+        Kind::Enum(_) => true,
+        _ => false,
+    }
+}
+
+/// Deserialize the raw value of an array with elements of type `member`
+/// as a sequence, multi-dimensional arrays as sequences of sequences.
+pub fn deserialize<'de, V>(member: &Type, raw: &[u8], config: &Config, visitor: V)
+    -> Result<V::Value>
+    where V: Visitor<'de>
+{
+    let array = RawArray::from_sql(raw)?;
+    let array = SubArray {
+        member,
+        config,
+        dimensions: &array.dimensions,
+        elements: &array.elements,
+    };
+    de::Deserializer::deserialize_seq(array, visitor)
+}
+
+/// The elements of an array along its remaining `dimensions`, in row-major
+/// order.
+struct SubArray<'a, 'b> {
+    member: &'a Type,
+    config: &'a Config,
+    dimensions: &'b [usize],
+    elements: &'b [Option<&'a [u8]>],
+}
+
+impl<'de, 'a, 'b> de::Deserializer<'de> for SubArray<'a, 'b> {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        if self.dimensions.len() <= 1 {
+            return visitor.visit_seq(Elements {
+                member: self.member,
+                config: self.config,
+                iter: self.elements.iter().cloned(),
+            });
+        }
+
+        // Postgres arrays are always rectangular, so every sub-array has the
+        // same number of elements.
+        let inner = &self.dimensions[1..];
+        let len = inner.iter().product::<usize>();
+        let member = self.member;
+        let config = self.config;
+        let sub_arrays = self.elements.chunks(len.max(1)).map(|elements| SubArray {
+            member,
+            config,
+            dimensions: inner,
+            elements,
+        });
+        visitor.visit_seq(de::value::SeqDeserializer::new(sub_arrays))
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 u8 u16 u32 u64 f32 f64 char str string bytes
+        byte_buf option unit unit_struct newtype_struct tuple tuple_struct
+        map struct enum identifier ignored_any
+    }
+}
+
+impl<'de, 'a, 'b> de::IntoDeserializer<'de, Error> for SubArray<'a, 'b> {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self {
+        self
+    }
+}
+
+struct Elements<'a, I> {
+    member: &'a Type,
+    config: &'a Config,
+    iter: I,
+}
+
+impl<'de, 'a, I> SeqAccess<'de> for Elements<'a, I>
+    where I: ExactSizeIterator<Item = Option<&'a [u8]>>
+{
+    type Error = Error;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(&mut self, seed: T)
+        -> Result<Option<T::Value>>
+    {
+        match self.iter.next() {
+            Some(raw) => seed.deserialize(Element {
+                ty: self.member,
+                raw,
+                config: self.config,
+            }).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.iter.len())
+    }
+}
+
+/// A single array element, or a range's bound, deserialized much like a
+/// column of its own.
+pub struct Element<'a> {
+    ty: &'a Type,
+    raw: Option<&'a [u8]>,
+    config: &'a Config,
+}
+
+impl<'a> Element<'a> {
+    /// An element of type `ty` with the raw value `raw`, `None` for `NULL`.
+    pub fn new(ty: &'a Type, raw: Option<&'a [u8]>, config: &'a Config) -> Self {
+        Element { ty, raw, config }
+    }
+
+    /// The label of a non-`NULL` Postgres enum or text element.
+    fn label(&self) -> Result<&'a str> {
+        let raw = self.raw.ok_or_else(|| Error::InvalidType(String::from("unexpected NULL")))?;
+        str::from_utf8(raw).map_err(|e| Error::InvalidType(e.to_string()))
+    }
+}
+
+macro_rules! element_value {
+    ($($fn_name:ident => $visit:ident, $ty:ty;)*) => {
+        $(
+            fn $fn_name<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+                visitor.$visit(raw::decode::<$ty>(self.ty, self.raw)?)
+            }
+        )*
+    }
+}
+
+macro_rules! element_integer {
+    ($($fn_name:ident => $visit:ident, $ty:ty;)*) => {
+        $(
+            fn $fn_name<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+                if self.config.coerce_integers && raw::is_integer(self.ty) {
+                    return raw::visit_integer(self.ty, self.raw, visitor);
+                }
+
+                visitor.$visit(raw::decode::<$ty>(self.ty, self.raw)?)
+            }
+        )*
+    }
+}
+
+impl<'de, 'a> de::Deserializer<'de> for Element<'a> {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        if self.raw.is_none() {
+            return visitor.visit_none();
+        }
+
+        match *self.ty {
+            Type::BOOL => self.deserialize_bool(visitor),
+            Type::INT2 => self.deserialize_i16(visitor),
+            Type::INT4 => self.deserialize_i32(visitor),
+            Type::INT8 => self.deserialize_i64(visitor),
+            Type::FLOAT4 => self.deserialize_f32(visitor),
+            Type::FLOAT8 => self.deserialize_f64(visitor),
+            Type::TEXT | Type::VARCHAR | Type::BPCHAR | Type::NAME =>
+                self.deserialize_string(visitor),
+            Type::BYTEA => self.deserialize_byte_buf(visitor),
+            Type::INET | Type::CIDR => self.deserialize_string(visitor),
+            Type::PG_LSN => self.deserialize_u64(visitor),
+            _ if is_enum(self.ty) => self.deserialize_string(visitor),
+            #[cfg(feature = "with-chrono")]
+            Type::TIMESTAMPTZ | Type::TIMESTAMP | Type::DATE | Type::TIME =>
+                self.deserialize_string(visitor),
+            _ => Err(Error::UnsupportedColumnType(self.ty.name().to_owned())),
+        }
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        match self.raw {
+            Some(_) => visitor.visit_some(self),
+            None => visitor.visit_none(),
+        }
+    }
+
+    fn deserialize_string<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        match (self.ty, self.raw) {
+            (&Type::INET, Some(raw)) | (&Type::CIDR, Some(raw)) =>
+                visitor.visit_string(Network::from_sql(raw)?.to_string()),
+            (&Type::PG_LSN, Some(raw)) => visitor.visit_string(Lsn::from_sql(raw)?.to_string()),
+            (ty, Some(_)) if is_enum(ty) => visitor.visit_str(self.label()?),
+            #[cfg(feature = "with-chrono")]
+            (&Type::TIMESTAMPTZ, _) | (&Type::TIMESTAMP, _) | (&Type::DATE, _) | (&Type::TIME, _) =>
+                match timestamp::to_text(self.ty, self.raw)? {
+                    Some(text) => visitor.visit_string(text),
+                    None => Err(Error::UnsupportedColumnType(self.ty.name().to_owned())),
+                },
+            _ => visitor.visit_string(raw::decode::<String>(self.ty, self.raw)?),
+        }
+    }
+
+    fn deserialize_str<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        self.deserialize_string(visitor)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(self,
+                                         _: &'static str,
+                                         variants: &'static [&'static str],
+                                         visitor: V)
+        -> Result<V::Value>
+    {
+        let label = self.config.enum_label(self.label()?, variants);
+        visitor.visit_enum(UnitVariant(label))
+    }
+
+    fn deserialize_u64<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        match (self.ty, self.raw) {
+            (&Type::PG_LSN, Some(raw)) => visitor.visit_u64(Lsn::from_sql(raw)?.0),
+            _ => self.deserialize_any(visitor),
+        }
+    }
+
+    element_value! {
+        deserialize_bool => visit_bool, bool;
+        deserialize_f32 => visit_f32, f32;
+        deserialize_f64 => visit_f64, f64;
+        deserialize_byte_buf => visit_byte_buf, Vec<u8>;
+    }
+
+    element_integer! {
+        deserialize_i8 => visit_i8, i8;
+        deserialize_i16 => visit_i16, i16;
+        deserialize_i32 => visit_i32, i32;
+        deserialize_i64 => visit_i64, i64;
+        deserialize_u32 => visit_u32, u32;
+    }
+
+    forward_to_deserialize_any! {
+        u8 u16 char bytes unit unit_struct newtype_struct seq tuple
+        tuple_struct map struct identifier ignored_any
+    }
+}
@@ -0,0 +1,57 @@
+//! Deserialize `bit` and `varbit` values as bit vectors.
+use bit_vec::BitVec;
+use serde::de::{DeserializeSeed, IntoDeserializer, MapAccess, Visitor};
+use serde::de::value::SeqDeserializer;
+use tokio_postgres::types::Type;
+
+use error::{Error, Result};
+
+/// The fields a `BitVec` is handed out as, in order.
+const FIELDS: [&str; 2] = ["storage", "nbits"];
+
+/// Whether `ty` is one of Postgres' bit string types.
+pub fn is_bits(ty: &Type) -> bool {
+    match *ty {
+        Type::BIT | Type::VARBIT => true,
+        _ => false,
+    }
+}
+
+/// Deserialize a bit string as a sequence of `bool`s, one per bit.
+pub fn deserialize_seq<'de, V: Visitor<'de>>(bits: BitVec, visitor: V) -> Result<V::Value> {
+    visitor.visit_seq(SeqDeserializer::new(bits.iter()))
+}
+
+/// Deserialize a bit string the way `BitVec` is serialized, as its storage
+/// blocks and its length in bits.
+pub fn deserialize_struct<'de, V: Visitor<'de>>(bits: BitVec, visitor: V) -> Result<V::Value> {
+    visitor.visit_map(Fields { bits, index: 0 })
+}
+
+struct Fields {
+    bits: BitVec,
+    index: usize,
+}
+
+impl<'de> MapAccess<'de> for Fields {
+    type Error = Error;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(&mut self, seed: K) -> Result<Option<K::Value>> {
+        match FIELDS.get(self.index) {
+            Some(field) => seed.deserialize(field.into_deserializer()).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value> {
+        self.index += 1;
+        match self.index {
+            1 => seed.deserialize(SeqDeserializer::new(self.bits.storage().iter().cloned())),
+            _ => seed.deserialize(self.bits.len().into_deserializer()),
+        }
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(FIELDS.len() - self.index)
+    }
+}
@@ -0,0 +1,160 @@
+//! Options controlling how Postgres rows are deserialized.
+use std::borrow::Cow;
+
+/// What to do with values whose Postgres type is unknown to both
+/// `serde_postgres` and `tokio_postgres`, such as types from extensions.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum UnknownFallback {
+    /// Fail like any other type mismatch: with `Error::Column` when the
+    /// target asks for a specific type, or `Error::UnsupportedColumnType`
+    /// when it would take a value of any type.
+    Error,
+    /// Hand the value to the target as text. This is only meaningful for
+    /// types whose binary representation is their text, e.g. enums.
+    Text,
+    /// Hand the value's binary representation to the target as raw bytes.
+    Bytes,
+}
+
+impl Default for UnknownFallback {
+    fn default() -> Self {
+        UnknownFallback::Error
+    }
+}
+
+/// How column names are normalized before being matched to struct fields.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ColumnCase {
+    /// Match column names exactly as Postgres returns them.
+    AsIs,
+    /// Convert column names to snake case first, so that a quoted
+    /// `"userName"` column matches a `user_name` field.
+    ToSnake,
+}
+
+impl ColumnCase {
+    /// Normalize a column name.
+    pub fn apply<'a>(&self, name: &'a str) -> Cow<'a, str> {
+        match *self {
+            ColumnCase::AsIs => Cow::Borrowed(name),
+            ColumnCase::ToSnake => Cow::Owned(to_snake_case(name)),
+        }
+    }
+}
+
+impl Default for ColumnCase {
+    fn default() -> Self {
+        ColumnCase::AsIs
+    }
+}
+
+/// Convert `camelCase` and `PascalCase` names to `snake_case`, keeping
+/// acronyms together, e.g. `userID` becomes `user_id`.
+fn to_snake_case(name: &str) -> String {
+    let chars: Vec<char> = name.chars().collect();
+    let mut snake = String::with_capacity(name.len() + 4);
+
+    for (i, &c) in chars.iter().enumerate() {
+        if c.is_uppercase() && i > 0 {
+            let prev = chars[i - 1];
+            let next_is_lower = chars.get(i + 1).map_or(false, |n| n.is_lowercase());
+            if prev.is_lowercase() || prev.is_numeric() || (prev.is_uppercase() && next_is_lower) {
+                snake.push('_');
+            }
+        }
+        snake.extend(c.to_lowercase());
+    }
+
+    snake
+}
+
+/// Options for a `Deserializer`.
+#[derive(Clone, Debug, Default)]
+pub struct Config {
+    pub(crate) unknown_type_fallback: UnknownFallback,
+    pub(crate) coerce_integers: bool,
+    pub(crate) column_case: ColumnCase,
+    pub(crate) geometry_as_wkt: bool,
+    pub(crate) coerce_money_to_f64: bool,
+    pub(crate) case_insensitive_enums: bool,
+    pub(crate) strict: bool,
+    pub(crate) null_as_default: bool,
+}
+
+impl Config {
+    /// Create a `Config` with every option set to its default.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set how values of unknown types are deserialized. Defaults to
+    /// `UnknownFallback::Error`.
+    pub fn unknown_type_fallback(mut self, fallback: UnknownFallback) -> Self {
+        self.unknown_type_fallback = fallback;
+        self
+    }
+
+    /// Allow any integer column, or array of integers, to be read into any
+    /// Rust integer type, e.g. `int2[]` into `Vec<i64>`. Values that don't
+    /// fit the target type are still an error. Defaults to `false`.
+    pub fn coerce_integers(mut self, coerce: bool) -> Self {
+        self.coerce_integers = coerce;
+        self
+    }
+
+    /// Set how column names are normalized before matching them to struct
+    /// fields. Defaults to `ColumnCase::AsIs`.
+    pub fn column_case(mut self, case: ColumnCase) -> Self {
+        self.column_case = case;
+        self
+    }
+
+    /// Read geometric columns (`point`, `lseg`, `box`, `path` and `polygon`)
+    /// into `String`s as Well-Known Text, e.g. `POINT(1 2)`. Defaults to
+    /// `false`.
+    pub fn geometry_as_wkt(mut self, wkt: bool) -> Self {
+        self.geometry_as_wkt = wkt;
+        self
+    }
+
+    /// Allow `money` columns to be read into `f64`, by dividing the amount in
+    /// cents by 100. This is lossy, so it defaults to `false`.
+    pub fn coerce_money_to_f64(mut self, coerce: bool) -> Self {
+        self.coerce_money_to_f64 = coerce;
+        self
+    }
+
+    /// Match enum labels to Rust enum variants ignoring ASCII case, e.g.
+    /// `'ACTIVE'` to a variant renamed to `active`. Defaults to `false`.
+    pub fn case_insensitive_enums(mut self, case_insensitive: bool) -> Self {
+        self.case_insensitive_enums = case_insensitive;
+        self
+    }
+
+    /// Fail with `unexpected column` for any column the target doesn't read,
+    /// rather than ignoring it. Defaults to `false`.
+    pub fn strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+
+    /// Read `NULL` into a non-`Option` boolean, number, character or string
+    /// as its type's default, e.g. `0` or `""`, rather than failing. Defaults
+    /// to `false`.
+    pub fn null_as_default(mut self, null_as_default: bool) -> Self {
+        self.null_as_default = null_as_default;
+        self
+    }
+
+    /// The variant of `variants` that the enum label `label` names, which is
+    /// `label` itself unless matching ignores case.
+    pub(crate) fn enum_label<'a>(&self, label: &'a str, variants: &'static [&'static str]) -> &'a str {
+        if !self.case_insensitive_enums {
+            return label;
+        }
+
+        variants.iter()
+            .find(|variant| variant.eq_ignore_ascii_case(label))
+            .map_or(label, |variant| *variant)
+    }
+}
@@ -4,358 +4,1922 @@ use serde::de::{
     Deserialize,
     Visitor,
     IntoDeserializer,
-    value::SeqDeserializer
+    value::{MapDeserializer, SeqDeserializer},
 };
 
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::error::Error as StdError;
+use std::marker::PhantomData;
+use std::mem;
+use std::str;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
 use tokio_postgres::row::Row;
+use tokio_postgres::types::{FromSql, Kind, Type};
+use array;
+#[cfg(feature = "bit-vec")]
+use bits;
+use config::{Config, UnknownFallback};
 use error::{Error, Result};
+#[cfg(feature = "with-geo-types")]
+use geo::Geometry;
+use epoch;
+#[cfg(feature = "hstore")]
+use hstore;
+use interval::{self, Interval};
+use lsn::Lsn;
+use money::Money;
+use network::Network;
+use numeric::Numeric;
+use range;
+#[cfg(feature = "with-num-rational")]
+use rational::Rational;
+use raw::{self, Raw, RawValue};
+#[cfg(feature = "with-chrono")]
+use timestamp;
+use suggest;
+use typed::{self, TypedAccess};
+use wkt;
+
+/// Field name that receives the whole row rather than a single column.
+///
+/// A struct field renamed to `*` is given every column of the row as a map
+/// of column name to value, e.g. to keep the raw row around for auditing:
+///
+/// ```rust,ignore
+/// #[derive(Deserialize)]
+/// struct Audit {
+///     id: i32,
+///     #[serde(rename = "*")]
+///     raw: serde_json::Value,
+/// }
+/// ```
+pub const WHOLE_ROW: &str = "*";
+
+/// A fallible transform applied to a column's value, given the column's
+/// name, before the value is decoded.
+pub type Transform = Box<dyn Fn(&str, RawValue) -> Result<RawValue>>;
+
+/// The row being deserialized, either owned or borrowed from the caller.
+enum Input<'a> {
+    Owned(Row),
+    Borrowed(&'a Row),
+}
+
+impl<'a> ::std::ops::Deref for Input<'a> {
+    type Target = Row;
+
+    fn deref(&self) -> &Row {
+        match *self {
+            Input::Owned(ref row) => row,
+            Input::Borrowed(row) => row,
+        }
+    }
+}
 
 /// A structure that deserialize Postgres rows into Rust values.
-pub struct Deserializer {
-    input: Row,
+pub struct Deserializer<'a> {
+    input: Input<'a>,
     index: usize,
+    whole_row: bool,
+    ordered: bool,
+    /// Whether a single column is being deserialized, rather than the row.
+    in_column: bool,
+    config: Config,
+    field_mapper: Option<Box<dyn Fn(&str) -> String + 'a>>,
+    /// Hands out the buffers `bytea` columns are read into.
+    buffer_pool: Option<BufferPool>,
+    /// Column names to read fields from instead of their own, keyed by the
+    /// field's name.
+    overrides: HashMap<String, String>,
+    /// Struct field names keyed by the column name they are read from, for
+    /// columns whose name differs from the field's.
+    fields: HashMap<String, &'static str>,
+    /// Keys already resolved for every column, shared by a
+    /// `CachedDeserializer` across rows with the same columns.
+    keys: Option<Arc<Vec<String>>>,
+    /// Every column's value after transforms, empty if there are none.
+    values: Vec<RawValue>,
+    /// Column indices keyed by column name, if built.
+    column_indices: Option<HashMap<String, usize>>,
 }
 
-impl Deserializer {
+impl<'a> Deserializer<'a> {
     /// Create a `Row` deserializer from a `Row`.
     pub fn from_row(input: Row) -> Self {
-        Self { index: 0, input }
+        Self::from_row_with_config(input, Config::default())
     }
-}
-
-/// Attempt to deserialize from a single `Row`.
-pub fn from_row<'a, T: Deserialize<'a>>(input: Row) -> Result<T> {
-    let mut deserializer = Deserializer::from_row(input);
-    Ok(T::deserialize(&mut deserializer)?)
-}
 
-/// Attempt to deserialize from `Rows`.
-pub fn from_rows<'a, T: Deserialize<'a>>(input: Vec<Row>) -> Result<Vec<T>> {
-    input.into_iter().map(|row| {
-        let mut deserializer = Deserializer::from_row(row);
-        T::deserialize(&mut deserializer)
-    }).collect()
-}
+    /// Create a `Row` deserializer from a `Row` with the given `Config`.
+    pub fn from_row_with_config(input: Row, config: Config) -> Self {
+        Self::from_input(Input::Owned(input), config)
+    }
 
-macro_rules! unsupported_type {
-    ($($fn_name:ident),*,) => {
-        $(
-            fn $fn_name<V: Visitor<'de>>(self, _: V) -> Result<V::Value> {
-                Err(Error::UnsupportedType)
-            }
-        )*
+    /// Create a `Row` deserializer borrowing a `Row`, so that the same row
+    /// can be deserialized more than once.
+    pub fn from_row_ref(input: &'a Row) -> Self {
+        Self::from_input(Input::Borrowed(input), Config::default())
     }
-}
 
-macro_rules! get_value {
-    ($this:ident, $v:ident, $fn_call:ident, $ty:ty) => {{
-        $v.$fn_call($this.input.try_get::<_, $ty>($this.index)
-            .map_err(|e| Error::InvalidType(format!("{:?}", e)))?)
-    }}
-}
+    fn from_input(input: Input<'a>, config: Config) -> Self {
+        Self {
+            index: 0,
+            input,
+            whole_row: false,
+            ordered: false,
+            in_column: false,
+            config,
+            field_mapper: None,
+            buffer_pool: None,
+            overrides: HashMap::new(),
+            fields: HashMap::new(),
+            keys: None,
+            values: Vec::new(),
+            column_indices: None,
+        }
+    }
 
-impl<'de, 'b> de::Deserializer<'de> for &'b mut Deserializer {
-    type Error = Error;
+    /// Build an index of the row's columns by name up front, making
+    /// `column_index` a hash lookup rather than a scan of every column.
+    pub fn with_column_index_cache(mut self) -> Self {
+        let mut indices = HashMap::with_capacity(self.input.columns().len());
+        for (index, column) in self.input.columns().iter().enumerate() {
+            indices.entry(column.name().to_owned()).or_insert(index);
+        }
+        self.column_indices = Some(indices);
+        self
+    }
 
-    unsupported_type! {
-        deserialize_any,
-        deserialize_u8,
-        deserialize_u16,
-        deserialize_u64,
-        deserialize_char,
-        deserialize_str,
-        deserialize_bytes,
-        deserialize_unit,
-        deserialize_identifier,
-        deserialize_option,
+    /// The index of the first column named `name`.
+    pub fn column_index(&self, name: &str) -> Option<usize> {
+        match self.column_indices {
+            Some(ref indices) => indices.get(name).cloned(),
+            None => self.input.columns().iter().position(|column| column.name() == name),
+        }
     }
 
-    fn deserialize_ignored_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
-        visitor.visit_unit()
+    /// The name of the column being deserialized. Only meaningful while
+    /// deserializing, e.g. from a custom `Deserialize` implementation;
+    /// `None` once every column has been read.
+    pub fn current_column_name(&self) -> Option<&str> {
+        self.input.columns().get(self.index).map(|c| c.name())
     }
 
-    fn deserialize_bool<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
-        get_value!(self, visitor, visit_bool, bool)
+    /// The Postgres type of the column being deserialized, with the same
+    /// caveats as `current_column_name`.
+    pub fn current_column_type(&self) -> Option<&Type> {
+        self.column_type()
     }
 
-    fn deserialize_i8<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
-        get_value!(self, visitor, visit_i8, i8)
+    /// The number of columns left to deserialize.
+    pub fn remaining_columns(&self) -> usize {
+        self.input.columns().len().saturating_sub(self.index)
     }
 
-    fn deserialize_i16<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
-        get_value!(self, visitor, visit_i16, i16)
+    /// Start over on another `Row`, keeping the configuration and any
+    /// buffers, e.g. to deserialize many rows with one `Deserializer`. The
+    /// column index cache is kept too, so `input` should have the same
+    /// columns as the previous row.
+    pub fn reset(&mut self, input: Row) {
+        self.input = Input::Owned(input);
+        self.index = 0;
+        self.whole_row = false;
+        self.in_column = false;
+        self.keys = None;
+        self.values.clear();
     }
 
-    fn deserialize_i32<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
-        get_value!(self, visitor, visit_i32, i32)
+    /// Run `transforms` over every column, in order, and decode the columns
+    /// from the results from then on.
+    fn transform(&mut self, transforms: &[Transform]) -> Result<()> {
+        let mut values = Vec::with_capacity(self.input.columns().len());
+        for (index, column) in self.input.columns().iter().enumerate() {
+            let raw = self.input.try_get::<_, Option<Raw>>(index)
+                .map_err(|e| self.column_error(index, e))?;
+            let mut value = RawValue::new(column.type_().clone(), raw.map(|raw| raw.0.to_vec()));
+            for transform in transforms {
+                value = transform(column.name(), value)?;
+            }
+            values.push(value);
+        }
+
+        self.values = values;
+        Ok(())
     }
 
-    fn deserialize_i64<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
-        get_value!(self, visitor, visit_i64, i64)
+    /// Decode the current column, from its transformed value if it has one.
+    fn get<'r, T: FromSql<'r>>(&'r self) -> Result<T> {
+        match self.values.get(self.index) {
+            Some(value) => raw::decode(value.ty(), value.bytes())
+                .map_err(|e| self.column_error(self.index, e)),
+            None => self.input.try_get(self.index)
+                .map_err(|e| self.column_error(self.index, e)),
+        }
     }
 
-    fn deserialize_u32<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
-        get_value!(self, visitor, visit_u32, u32)
+    /// Wrap an error reading the column at `index`, from `tokio_postgres` or
+    /// from decoding its transformed value.
+    fn column_error<E>(&self, index: usize, error: E) -> Error
+        where E: StdError + Send + Sync + 'static
+    {
+        Error::Column {
+            name: self.input.columns().get(index).map_or_else(String::new, |c| c.name().to_owned()),
+            source: Arc::new(error),
+        }
     }
 
-    fn deserialize_f32<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
-        get_value!(self, visitor, visit_f32, f32)
+    /// The key every column is handed out under, after applying the
+    /// configured column case and any field mapping.
+    fn resolved_keys(&self) -> Vec<String> {
+        self.input.columns().iter().map(|column| {
+            let name = self.config.column_case.apply(column.name());
+            self.fields.get(&*name).map_or(&*name, |field| *field).to_owned()
+        }).collect()
     }
 
-    fn deserialize_f64<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
-        get_value!(self, visitor, visit_f64, f64)
+    /// Deserialize the current column and move on to the next one, adding
+    /// the column's name to type errors.
+    fn next_column<'de, T>(&mut self, seed: T) -> Result<T::Value>
+        where T: de::DeserializeSeed<'de>, 'a: 'de
+    {
+        let in_column = mem::replace(&mut self.in_column, true);
+        let result = seed.deserialize(&mut *self);
+        self.in_column = in_column;
+        self.index += 1;
+        let name = self.input.columns().get(self.index - 1).unwrap().name();
+        match result {
+            Err(Error::InvalidType(err)) => Err(Error::InvalidType(format!("{} {}", name, err))),
+            result => result,
+        }
     }
 
-    fn deserialize_string<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
-        get_value!(self, visitor, visit_string, String)
+    /// The error for a column looked up past the last one.
+    fn column_not_found(&self) -> Error {
+        Error::ColumnNotFound { name: String::new(), index: self.index }
     }
 
-    fn deserialize_byte_buf<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
-        get_value!(self, visitor, visit_byte_buf, Vec<u8>)
+    /// Turn serde's error for a field with no column into a
+    /// `ColumnNotFound`, or name the unused column closest to the field's
+    /// name, in case the field's name is a typo.
+    fn suggest(&self, err: Error, fields: &[&str]) -> Error {
+        let field = match err {
+            Error::Message(ref msg) => msg.strip_prefix("missing field `")
+                .and_then(|rest| rest.strip_suffix('`')),
+            _ => None,
+        };
+        let field = match field {
+            Some(field) => field,
+            None => return err,
+        };
+        let not_found = Error::ColumnNotFound {
+            name: field.to_owned(),
+            index: fields.iter().position(|f| *f == field).unwrap_or(0),
+        };
+
+        let columns: Vec<_> = self.input.columns().iter()
+            .map(|column| self.config.column_case.apply(column.name()))
+            .filter(|name| !fields.contains(&&**name))
+            .collect();
+
+        match suggest::closest(field, columns.iter().map(|name| &**name)) {
+            Some(column) => Error::Message(format!(
+                "missing field `{}`; did you mean `{}`?", field, column)),
+            None => not_found,
+        }
     }
 
-    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
-        let raw = self.input.try_get::<_, Vec<u8>>(self.index)
-            .map_err(|e| Error::InvalidType(format!("{:?}", e)))?;
+    /// Whether the column at `index` is named after a field that an override
+    /// or the field mapper reads from another column instead, so that it
+    /// must be skipped.
+    fn is_shadowed(&self, index: usize) -> bool {
+        if self.fields.is_empty() || self.keys.is_some() {
+            return false;
+        }
 
-        visitor.visit_seq(SeqDeserializer::new(raw.into_iter()))
+        let name = self.config.column_case.apply(self.input.columns()[index].name());
+        !self.fields.contains_key(&*name) && self.fields.values().any(|field| *field == &*name)
     }
 
+    /// Whether the current column is SQL `NULL`.
+    fn is_null(&self) -> Result<bool> {
+        self.raw_nullable().map(|raw| raw.is_none())
+    }
 
-    fn deserialize_enum<V: Visitor<'de>>(self,
-                                         _: &str,
-                                         _: &[&str],
-                                         _visitor: V)
-        -> Result<V::Value>
-    {
-        //visitor.visit_enum(self)
-        Err(Error::UnsupportedType)
+    /// The Postgres type of the current column.
+    fn column_type(&self) -> Option<&Type> {
+        self.input.columns().get(self.index).map(|c| c.type_())
     }
 
-    fn deserialize_unit_struct<V: Visitor<'de>>(self, _: &str, _: V)
-        -> Result<V::Value>
-    {
-        Err(Error::UnsupportedType)
+    /// Decode the current column borrowing from the row itself, `None` if
+    /// the row is owned or its columns were transformed.
+    fn borrowed<T: FromSql<'a>>(&self) -> Option<Result<T>> {
+        match self.input {
+            Input::Borrowed(row) if self.values.is_empty() => Some(row.try_get(self.index)
+                .map_err(|e| self.column_error(self.index, e))),
+            _ => None,
+        }
     }
 
-    fn deserialize_newtype_struct<V: Visitor<'de>>(self, _: &str, _: V)
-        -> Result<V::Value>
-    {
-        Err(Error::UnsupportedType)
+    /// Read the current column's undecoded binary value, `None` if `NULL`.
+    fn raw_nullable(&self) -> Result<Option<&[u8]>> {
+        self.get::<Option<Raw>>().map(|raw| raw.map(|raw| raw.0))
     }
 
-    fn deserialize_tuple<V: Visitor<'de>>(self, _: usize, _: V)
-        -> Result<V::Value>
-    {
-        Err(Error::UnsupportedType)
+    /// Read the current column's undecoded binary value.
+    fn raw(&self) -> Result<&[u8]> {
+        self.get::<Raw>().map(|raw| raw.0)
     }
 
-    fn deserialize_tuple_struct<V: Visitor<'de>>(self,
-                                                 _: &str,
-                                                 _: usize,
-                                                 _: V)
-        -> Result<V::Value>
-    {
-        Err(Error::UnsupportedType)
+    /// The fallback to apply to the current column: the configured one if
+    /// its type is unknown to `tokio_postgres`, `UnknownFallback::Error`
+    /// otherwise.
+    fn fallback(&self) -> UnknownFallback {
+        match self.column_type() {
+            Some(ty) if Type::from_oid(ty.oid()).is_none() =>
+                self.config.unknown_type_fallback,
+            _ => UnknownFallback::Error,
+        }
     }
 
-    fn deserialize_map<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
-        visitor.visit_map(self)
+    /// Read the current column's undecoded binary value as text.
+    fn raw_text(&self) -> Result<&str> {
+        str::from_utf8(self.raw()?)
+            .map_err(|e| Error::InvalidType(e.to_string()))
     }
 
-    fn deserialize_struct<V: Visitor<'de>>(self, _: &'static str, _: &'static [&'static str], v: V) -> Result<V::Value> {
-        self.deserialize_map(v)
+    /// Whether the current column is an integer that should be coerced into
+    /// whichever integer type the target asks for.
+    fn coerce_integer(&self) -> bool {
+        self.config.coerce_integers && self.column_type().map_or(false, raw::is_integer)
     }
-}
 
-impl<'de> de::MapAccess<'de> for Deserializer {
-    type Error = Error;
+    /// Visit the current column's raw bytes as a sequence of exactly `len`
+    /// bytes.
+    fn visit_byte_array<'de, V: Visitor<'de>>(&self, len: usize, visitor: V) -> Result<V::Value> {
+        let raw = self.raw()?;
+        if raw.len() != len {
+            return Err(Error::InvalidType(format!(
+                "expected {} bytes, found {}", len, raw.len())));
+        }
 
-    fn next_key_seed<T: de::DeserializeSeed<'de>>(&mut self, seed: T)
-        -> Result<Option<T::Value>>
+        visitor.visit_seq(SeqDeserializer::new(raw.iter().cloned()))
+    }
+
+    /// Visit the current integer column as its matching Rust type.
+    fn visit_integer<'de, V: Visitor<'de>>(&self, visitor: V) -> Result<V::Value> {
+        match self.column_type() {
+            Some(ty) => raw::visit_integer(ty, self.raw_nullable()?, visitor),
+            None => Err(self.column_not_found()),
+        }
+    }
+
+    /// Read the current integer or JSON number column into an unsigned type,
+    /// failing with `message` if an integer column's value doesn't fit.
+    fn unsigned<T>(&self, message: &str) -> Result<T>
+        where T: TryFrom<i64> + de::DeserializeOwned
     {
-        if self.index >= self.input.columns().len() {
-            return Ok(None)
+        #[cfg(feature = "json")]
+        {
+            if self.is_json() {
+                return T::deserialize(self.json_value()?)
+                    .map_err(|e| Error::InvalidType(e.to_string()));
+            }
         }
 
-        self.input.columns()
-            .get(self.index)
-            .ok_or(Error::UnknownField)
-            .map(|c| c.name().to_owned().into_deserializer())
-            .and_then(|n| seed.deserialize(n).map(Some))
+        let ty = self.column_type().ok_or_else(|| self.column_not_found())?;
+        let value = raw::integer(ty, self.raw_nullable()?)?;
+        T::try_from(value).map_err(|_| Error::InvalidType(message.to_owned()))
+    }
 
+    /// Decode the current column if it holds a geometric value.
+    #[cfg(feature = "with-geo-types")]
+    fn geometry(&self) -> Result<Option<Geometry>> {
+        match self.column_type() {
+            Some(ty) => Geometry::from_sql(ty, self.raw_nullable()?),
+            None => Ok(None),
+        }
     }
 
-    fn next_value_seed<T: de::DeserializeSeed<'de>>(&mut self, seed: T)
-        -> Result<T::Value>
-    {
-        let result = seed.deserialize(&mut *self);
-        self.index += 1;
-        if let Err(Error::InvalidType(err)) = result {
-            let name = self.input.columns().get(self.index - 1).unwrap().name();
-            Err(Error::InvalidType(format!("{} {}", name, err)))
-        } else {
-            result
+    /// Decode the current column if it holds an `inet` or `cidr`.
+    fn network(&self) -> Result<Option<Network>> {
+        match self.column_type() {
+            Some(&Type::INET) | Some(&Type::CIDR) => Network::from_sql(self.raw()?).map(Some),
+            _ => Ok(None),
         }
     }
-}
 
-/*
-impl<'de, 'a, 'b> de::EnumAccess<'de> for &'b mut Deserializer<'a> {
-    type Error = Error;
-    type Variant = Self;
+    /// Whether the current column holds a `json` or `jsonb` value.
+    #[cfg(feature = "json")]
+    fn is_json(&self) -> bool {
+        match self.column_type() {
+            Some(&Type::JSON) | Some(&Type::JSONB) => true,
+            _ => false,
+        }
+    }
 
-    fn variant_seed<V: de::DeserializeSeed<'de>>(self, seed: V)
-        -> Result<(V::Value, Self::Variant)>
-    {
-        let value = seed.deserialize(self);
+    /// Read the current column as a `serde_json::Value`, so the target can
+    /// be deserialized by `serde_json` rather than from the row itself.
+    #[cfg(feature = "json")]
+    fn json_value(&self) -> Result<::serde_json::Value> {
+        self.get()
+    }
+
+    /// Deserialize the current `json` column's number into a float. JSON has
+    /// no NaN or infinities, and `serde_json` writes them as `null`, so
+    /// `null` is read back as NaN.
+    #[cfg(feature = "json")]
+    fn json_float<'de, V: Visitor<'de>>(&self, visitor: V) -> Result<V::Value> {
+        match self.json_value()? {
+            ::serde_json::Value::Null => visitor.visit_f64(::std::f64::NAN),
+            value => de::Deserializer::deserialize_any(value, visitor)
+                .map_err(|e| Error::InvalidType(e.to_string())),
+        }
     }
 }
 
-impl<'de, 'a, 'b> de::VariantAccess<'de> for &'b mut Deserializer<'a> {
-    type Error = Error;
+/// Builds `Deserializer`s with a `Config`, one row at a time.
+#[derive(Clone, Debug, Default)]
+pub struct DeserializerBuilder {
+    config: Config,
+}
 
-    fn unit_variant(self) -> Result<()> {
-        Ok(())
+impl DeserializerBuilder {
+    /// Create a `DeserializerBuilder` with the default `Config`.
+    pub fn new() -> Self {
+        Self::default()
     }
 
-    fn newtype_variant_seed<T: de::DeserializeSeed<'de>>(self, seed: T)
-        -> Result<T::Value>
-    {
-        self.input.get_opt::<_, T::Value>(self.index)
-            .unwrap()
-            .map_err(|_| Error::InvalidType)
+    /// Use `config`, replacing any options set so far.
+    pub fn config(mut self, config: Config) -> Self {
+        self.config = config;
+        self
     }
 
-    fn tuple_variant<V: Visitor<'de>>(self, _: usize, _: V)
-        -> Result<V::Value>
-    {
-        unimplemented!("tuple_variant")
+    /// See `Config::null_as_default`.
+    pub fn null_as_default(mut self, null_as_default: bool) -> Self {
+        self.config = self.config.null_as_default(null_as_default);
+        self
     }
 
-    fn struct_variant<V: Visitor<'de>>(self, _: &[&str], _: V)
-        -> Result<V::Value>
-    {
-        unimplemented!("struct_variant")
+    /// Create a `Deserializer` for `input`.
+    pub fn build<'a>(&self, input: Row) -> Deserializer<'a> {
+        Deserializer::from_row_with_config(input, self.config.clone())
+    }
+
+    /// Create a `Deserializer` borrowing `input`.
+    pub fn build_ref<'a>(&self, input: &'a Row) -> Deserializer<'a> {
+        Deserializer::from_input(Input::Borrowed(input), self.config.clone())
     }
 }
-*/
 
-#[cfg(test)]
-mod tests {
-    use std::env;
+/// Deserialize an `Option<T>` field that must not be `NULL` in the current
+/// query, for use with `#[serde(deserialize_with = "...")]`.
+///
+/// A `NULL` column is an error rather than `None`, so a struct shared with
+/// lenient queries can still be read strictly where the business rules
+/// forbid `NULL`.
+///
+/// ```rust,ignore
+/// #[derive(Deserialize)]
+/// struct Account {
+///     #[serde(deserialize_with = "serde_postgres::de::non_null")]
+///     email: Option<String>,
+/// }
+/// ```
+pub fn non_null<'de, D, T>(deserializer: D) -> ::std::result::Result<Option<T>, D::Error>
+    where D: de::Deserializer<'de>,
+          T: Deserialize<'de>,
+{
+    T::deserialize(deserializer).map(Some)
+}
 
-    use serde_derive::Deserialize;
+/// Microseconds since the Unix epoch of the first and last instants of the
+/// years -9999 to 9999, which is all `time` can represent by default.
+#[cfg(feature = "with-time")]
+const TIME_MICROS: (i64, i64) = (-377_705_203_200_000_000, 253_402_300_799_999_999);
 
-    use postgres::Connection;
+/// Read a date or time column as microseconds, for the `time_*` functions.
+#[cfg(feature = "with-time")]
+fn unix_micros<'de, D>(deserializer: D) -> ::std::result::Result<i64, D::Error>
+    where D: de::Deserializer<'de>,
+{
+    struct MicrosVisitor;
 
-    fn setup_and_connect_to_db() -> Connection {
-        let user = env::var("PGUSER").unwrap_or("postgres".into());
-        let pass = env::var("PGPASSWORD").map(|p| format!("{}", p)).unwrap_or("postgres".into());
-        let addr = env::var("PGADDR").unwrap_or("localhost".into());
-        let port = env::var("PGPORT").unwrap_or("5432".into());
-        let url = format!("postgres://{user}:{pass}@{addr}:{port}", user = user, pass = pass, addr = addr, port = port);
-        Connection::connect(url, postgres::TlsMode::None).unwrap()
-    }
+    impl<'de> Visitor<'de> for MicrosVisitor {
+        type Value = i64;
 
-    #[test]
-    fn non_null() {
-        #[derive(Debug, Deserialize, PartialEq)]
-        struct Buu {
-            wants_candy: bool,
-            width: i16,
-            amount_eaten: i32,
-            amount_want_to_eat: i64,
-            speed: f32,
-            weight: f64,
-            catchphrase: String,
-            stomach_contents: Vec<u8>,
+        fn expecting(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+            f.write_str("a date or time")
         }
 
-        let connection = setup_and_connect_to_db();
+        fn visit_i64<E: de::Error>(self, v: i64) -> ::std::result::Result<Self::Value, E> {
+            Ok(v)
+        }
+    }
 
-        connection.execute("CREATE TABLE IF NOT EXISTS Buu (
-                    wants_candy BOOL NOT NULL,
-                    width SMALLINT NOT NULL,
-                    amount_eaten INT NOT NULL,
-                    amount_want_to_eat BIGINT NOT NULL,
-                    speed REAL NOT NULL,
-                    weight DOUBLE PRECISION NOT NULL,
-                    catchphrase VARCHAR NOT NULL,
-                    stomach_contents BYTEA NOT NULL
-        )", &[]).unwrap();
+    deserializer.deserialize_newtype_struct(epoch::UNIX_MICROS, MicrosVisitor)
+}
 
-        connection.execute("INSERT INTO Buu (
-            wants_candy,
-            width,
-            amount_eaten,
-            amount_want_to_eat,
-            speed,
-            weight,
-            catchphrase,
-            stomach_contents
-        ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8)",
-        &[&true, &20i16, &1000i32, &1000_000i64, &99.99f32, &9999.9999f64, &String::from("Woo Woo"), &vec![1u8, 2, 3, 4, 5, 6]]).unwrap();
+/// Deserialize a `timestamptz` into a `time::OffsetDateTime` in UTC, for use
+/// with `#[serde(deserialize_with = "...")]`. `time`'s own `Deserialize`
+/// implementations expect its own serialized form rather than a column.
+#[cfg(feature = "with-time")]
+pub fn time_offset_date_time<'de, D>(deserializer: D)
+    -> ::std::result::Result<::time::OffsetDateTime, D::Error>
+    where D: de::Deserializer<'de>,
+{
+    let micros = unix_micros(deserializer)?;
+    if micros < TIME_MICROS.0 || micros > TIME_MICROS.1 {
+        return Err(de::Error::custom("value out of range for the time crate"));
+    }
 
-        let results = connection.query("SELECT wants_candy,
-            width,
-            amount_eaten,
-            amount_want_to_eat,
-            speed,
-            weight,
-            catchphrase,
-            stomach_contents
- FROM Buu", &[]).unwrap();
+    Ok(::time::OffsetDateTime::unix_epoch() + ::time::Duration::microseconds(micros))
+}
 
-        let row = results.get(0);
+/// Deserialize a `timestamp` into a `time::PrimitiveDateTime`, like
+/// `time_offset_date_time`.
+#[cfg(feature = "with-time")]
+pub fn time_primitive_date_time<'de, D>(deserializer: D)
+    -> ::std::result::Result<::time::PrimitiveDateTime, D::Error>
+    where D: de::Deserializer<'de>,
+{
+    time_offset_date_time(deserializer)
+        .map(|timestamp| ::time::PrimitiveDateTime::new(timestamp.date(), timestamp.time()))
+}
 
-        let buu: Buu = super::from_row(row).unwrap();
+/// Deserialize a `date` into a `time::Date`, like `time_offset_date_time`.
+#[cfg(feature = "with-time")]
+pub fn time_date<'de, D>(deserializer: D) -> ::std::result::Result<::time::Date, D::Error>
+    where D: de::Deserializer<'de>,
+{
+    time_offset_date_time(deserializer).map(|timestamp| timestamp.date())
+}
 
-        assert_eq!(true, buu.wants_candy);
-        assert_eq!(20, buu.width);
-        assert_eq!(1000, buu.amount_eaten);
-        assert_eq!(1000_000, buu.amount_want_to_eat);
-        assert_eq!(99.99, buu.speed);
-        assert_eq!(9999.9999, buu.weight);
-        assert_eq!("Woo Woo", buu.catchphrase);
-        assert_eq!(vec![1,2,3,4,5,6], buu.stomach_contents);
+/// Deserialize a `time` into a `time::Time`, like `time_offset_date_time`.
+#[cfg(feature = "with-time")]
+pub fn time_time<'de, D>(deserializer: D) -> ::std::result::Result<::time::Time, D::Error>
+    where D: de::Deserializer<'de>,
+{
+    unix_micros(deserializer)
+        .map(|micros| ::time::Time::midnight() + ::time::Duration::microseconds(micros))
+}
 
-        connection.execute("DROP TABLE Buu", &[]).unwrap();
-    }
+/// Deserialize an `interval` into a `chrono::Duration`, for use with
+/// `#[serde(deserialize_with = "...")]` since `chrono::Duration` has no
+/// `Deserialize` implementation of its own.
+///
+/// Negative intervals are fine, but intervals with months are an error as
+/// months have no fixed length.
+#[cfg(feature = "with-chrono")]
+pub fn chrono_duration<'de, D>(deserializer: D) -> ::std::result::Result<::chrono::Duration, D::Error>
+    where D: de::Deserializer<'de>,
+{
+    struct DurationVisitor;
 
-    #[test]
-    fn nullable() {
-        #[derive(Debug, Deserialize, PartialEq)]
-        struct Buu {
-            wants_candy: Option<bool>,
-            width: Option<i16>,
-            amount_eaten: Option<i32>,
-            amount_want_to_eat: Option<i64>,
-            speed: Option<f32>,
-            weight: Option<f64>,
-            catchphrase: Option<String>,
-            stomach_contents: Option<Vec<u8>>,
+    impl<'de> Visitor<'de> for DurationVisitor {
+        type Value = ::chrono::Duration;
+
+        fn expecting(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+            f.write_str("an interval")
         }
 
-        let connection = setup_and_connect_to_db();
+        fn visit_i64<E: de::Error>(self, v: i64) -> ::std::result::Result<Self::Value, E> {
+            Ok(::chrono::Duration::microseconds(v))
+        }
+    }
 
-        connection.execute("CREATE TABLE IF NOT EXISTS NullBuu (
-                    wants_candy BOOL,
-                    width SMALLINT,
-                    amount_eaten INT,
-                    amount_want_to_eat BIGINT,
-                    speed REAL,
-                    weight DOUBLE PRECISION,
-                    catchphrase VARCHAR,
-                    stomach_contents BYTEA
-        )", &[]).unwrap();
+    deserializer.deserialize_newtype_struct(interval::DURATION, DurationVisitor)
+}
 
-        connection.execute("INSERT INTO NullBuu (
-            wants_candy,
-            width,
-            amount_eaten,
-            amount_want_to_eat,
-            speed,
+/// Deserialize an `inet` or `cidr` into an `ipnet::IpNet`, for use with
+/// `#[serde(deserialize_with = "...")]`.
+///
+/// `IpNet` fields also work without this for `cidr` columns, but Postgres
+/// leaves the prefix length off `inet` host addresses and `IpNet` won't
+/// parse those. Here they're read as a network of just that host, e.g.
+/// `192.168.1.1/32`. Both `ipnet` and `ipnetwork` types read `inet` and
+/// `cidr` through their text form, so they can be used side by side.
+#[cfg(feature = "with-ipnet")]
+pub fn ipnet<'de, D>(deserializer: D) -> ::std::result::Result<::ipnet::IpNet, D::Error>
+    where D: de::Deserializer<'de>,
+{
+    let network = String::deserialize(deserializer)?;
+    network.parse::<::ipnet::IpNet>()
+        .or_else(|_| network.parse::<::std::net::IpAddr>().map(::ipnet::IpNet::from))
+        .map_err(|_| de::Error::custom(format!("invalid network {}", network)))
+}
+
+/// Check that `row` has a column for each of `names`, e.g. to bail out with
+/// a friendlier error before attempting to deserialize it.
+pub fn row_has_columns(row: &Row, names: &[&str]) -> bool {
+    names.iter().all(|name| row.columns().iter().any(|c| c.name() == *name))
+}
+
+/// Attempt to deserialize from a single `Row`.
+pub fn from_row<'a, T: Deserialize<'a>>(input: Row) -> Result<T> {
+    let mut deserializer = Deserializer::from_row(input);
+    Ok(T::deserialize(&mut deserializer)?)
+}
+
+/// Attempt to deserialize from a borrowed `Row`, leaving it to be
+/// deserialized again, e.g. into another type. Text columns can be borrowed
+/// from the row as `&str`.
+pub fn from_row_ref<'a, T: Deserialize<'a>>(input: &'a Row) -> Result<T> {
+    let mut deserializer = Deserializer::from_row_ref(input);
+    Ok(T::deserialize(&mut deserializer)?)
+}
+
+/// Attempt to deserialize from a single `Row` with the given `Config`.
+pub fn from_row_with_config<'a, T: Deserialize<'a>>(input: Row, config: Config) -> Result<T> {
+    let mut deserializer = Deserializer::from_row_with_config(input, config);
+    Ok(T::deserialize(&mut deserializer)?)
+}
+
+/// Attempt to deserialize from a single `Row`, listing the row's columns and
+/// their types in the error message on failure, e.g. to spot a mismatch
+/// between a query and the struct it's read into.
+///
+/// `Message` and `InvalidType` errors have the columns appended to their
+/// message, any other error is wrapped in `Error::WithColumns`.
+pub fn from_row_debug<'a, T: Deserialize<'a>>(input: Row) -> Result<T> {
+    let mut deserializer = Deserializer::from_row(input);
+    T::deserialize(&mut deserializer).map_err(|err| {
+        let columns: Vec<_> = deserializer.input.columns().iter()
+            .map(|column| format!("{} {}", column.name(), column.type_().name()))
+            .collect();
+        let columns = columns.join(", ");
+
+        match err {
+            Error::Message(msg) => Error::Message(format!("{} (columns: {})", msg, columns)),
+            Error::InvalidType(msg) => Error::InvalidType(format!("{} (columns: {})", msg, columns)),
+            err => Error::WithColumns { columns, source: Box::new(err) },
+        }
+    })
+}
+
+/// Attempt to deserialize from a single `Row`, failing if any of its columns
+/// isn't read by `T`.
+pub fn from_row_strict<'a, T: Deserialize<'a>>(input: Row) -> Result<T> {
+    from_row_with_config(input, Config::new().strict(true))
+}
+
+/// Attempt to deserialize the first column of a single `Row`, e.g. the
+/// result of `SELECT count(*)`, into a bare value rather than a struct.
+pub fn from_row_scalar<'a, T: Deserialize<'a>>(input: Row) -> Result<T> {
+    let mut deserializer = Deserializer::from_row(input);
+    if deserializer.input.columns().is_empty() {
+        return Err(deserializer.column_not_found());
+    }
+
+    deserializer.next_column(PhantomData)
+}
+
+/// Attempt to deserialize a struct from a single `Row`, matching columns to
+/// fields by position instead of by name.
+///
+/// The query must select the struct's fields in the order they are
+/// declared. In exchange no column names are looked at, which makes this
+/// cheaper than `from_row` on hot paths.
+pub fn from_row_ordered<'a, T: Deserialize<'a>>(input: Row) -> Result<T> {
+    let mut deserializer = Deserializer::from_row(input);
+    deserializer.ordered = true;
+    Ok(T::deserialize(&mut deserializer)?)
+}
+
+/// Attempt to deserialize a struct from a single `Row`, reading each field
+/// from the column whose name `mapper` gives for the field's name.
+///
+/// This applies one naming convention to every field at runtime, e.g. a
+/// table prefix with `|field| format!("user_{}", field)`. Columns that no
+/// field maps to are ignored, as with `from_row`, and so is a column named
+/// after a field that's mapped to another column.
+pub fn from_row_with_field_mapper<'a, T, F>(input: Row, mapper: F) -> Result<T>
+    where T: Deserialize<'a>,
+          F: Fn(&str) -> String + 'a,
+{
+    let mut deserializer = Deserializer::from_row(input);
+    deserializer.field_mapper = Some(Box::new(mapper));
+    Ok(T::deserialize(&mut deserializer)?)
+}
+
+/// Attempt to deserialize a struct from a single `Row`, reading the fields
+/// named in `overrides` from the column it maps them to.
+///
+/// This lets one struct be read from queries using different aliases. The
+/// field names are the ones serde knows the fields by, i.e. their
+/// `#[serde(rename)]` if they have one. So a field is read from the column
+/// given by `overrides`, else by its rename, else by its own name. A column
+/// named after an overridden field is ignored, even if the row has it.
+pub fn from_row_with_overrides<'a, T>(input: Row, overrides: &HashMap<&str, &str>) -> Result<T>
+    where T: Deserialize<'a>,
+{
+    let mut deserializer = Deserializer::from_row(input);
+    deserializer.overrides = overrides.iter()
+        .map(|(field, column)| (field.to_string(), column.to_string()))
+        .collect();
+    Ok(T::deserialize(&mut deserializer)?)
+}
+
+/// Attempt to deserialize from a `Row`, running each column's value through
+/// `transforms` first, e.g. to trim whitespace off text columns.
+///
+/// Transforms are applied in order and see the column's name and its value
+/// before it's decoded. An error from any transform fails the row.
+pub fn from_row_with_transforms<'a, T>(input: Row, transforms: &[Transform]) -> Result<T>
+    where T: Deserialize<'a>,
+{
+    let mut deserializer = Deserializer::from_row(input);
+    deserializer.transform(transforms)?;
+    Ok(T::deserialize(&mut deserializer)?)
+}
+
+/// Attempt to deserialize from `Rows`.
+pub fn from_rows<'a, T: Deserialize<'a>>(input: Vec<Row>) -> Result<Vec<T>> {
+    from_rows_iter(input).collect()
+}
+
+/// Attempt to deserialize from `Rows` with a single `Deserializer`, reset
+/// for every row rather than created anew.
+pub fn from_rows_reuse<'a, T: Deserialize<'a>>(input: Vec<Row>) -> Result<Vec<T>> {
+    let mut output = Vec::with_capacity(input.len());
+    let mut rows = input.into_iter();
+    let mut deserializer = match rows.next() {
+        Some(row) => Deserializer::from_row(row),
+        None => return Ok(output),
+    };
+
+    output.push(T::deserialize(&mut deserializer)?);
+    for row in rows {
+        deserializer.reset(row);
+        output.push(T::deserialize(&mut deserializer)?);
+    }
+
+    Ok(output)
+}
+
+/// Deserialize every row of a stream, such as a `tokio_postgres::RowStream`,
+/// as it arrives, without collecting the rows first.
+#[cfg(feature = "async")]
+pub fn from_row_stream<'a, T, S>(stream: S) -> impl ::futures::Stream<Item = Result<T>>
+    where T: Deserialize<'a>,
+          S: ::futures::Stream<Item = ::std::result::Result<Row, ::tokio_postgres::Error>>,
+{
+    use futures::StreamExt;
+
+    stream.map(|row| from_row(row?))
+}
+
+/// Lazily deserialize `Rows`, one row at a time, e.g. to stop at the first
+/// error or to avoid holding every deserialized row at once.
+pub fn from_rows_iter<'a, T, I>(input: I) -> impl Iterator<Item = Result<T>>
+    where T: Deserialize<'a>,
+          I: IntoIterator<Item = Row>,
+{
+    input.into_iter().map(|row| {
+        let mut deserializer = Deserializer::from_row(row);
+        T::deserialize(&mut deserializer)
+    })
+}
+
+/// Attempt to deserialize from `Rows`, reporting how long each row took.
+///
+/// `on_row` is called after every successfully deserialized row with the
+/// row's index and the time spent deserializing it. Use `from_rows` when no
+/// timing is needed.
+pub fn from_rows_with_metrics<'a, T, F>(input: Vec<Row>, mut on_row: F) -> Result<Vec<T>>
+    where T: Deserialize<'a>,
+          F: FnMut(usize, Duration),
+{
+    input.into_iter().enumerate().map(|(index, row)| {
+        let start = Instant::now();
+        let mut deserializer = Deserializer::from_row(row);
+        let value = T::deserialize(&mut deserializer)?;
+        on_row(index, start.elapsed());
+        Ok(value)
+    }).collect()
+}
+
+/// Buffers for `from_rows_with_buffer_pool` to read `bytea` columns into.
+///
+/// A buffer is taken out of the pool for the value it's read into, so it's
+/// never shared. Once the value is no longer needed, its buffer can be put
+/// back to be reused by the next call.
+#[derive(Clone, Debug, Default)]
+pub struct BufferPool {
+    buffers: Vec<Vec<u8>>,
+}
+
+impl BufferPool {
+    /// Create an empty `BufferPool`.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Put `buffer` into the pool, to be cleared and reused.
+    pub fn put(&mut self, buffer: Vec<u8>) {
+        self.buffers.push(buffer);
+    }
+
+    /// The number of buffers in the pool.
+    pub fn len(&self) -> usize {
+        self.buffers.len()
+    }
+
+    /// Whether the pool has no buffers left.
+    pub fn is_empty(&self) -> bool {
+        self.buffers.is_empty()
+    }
+
+    /// Take an empty buffer with room for `len` bytes out of the pool, or
+    /// allocate one if the pool is empty.
+    fn take(&mut self, len: usize) -> Vec<u8> {
+        match self.buffers.pop() {
+            Some(mut buffer) => {
+                buffer.clear();
+                buffer.reserve(len);
+                buffer
+            }
+            None => Vec::with_capacity(len),
+        }
+    }
+}
+
+/// Attempt to deserialize from `Rows`, reading `bytea` columns into buffers
+/// taken from `pool` rather than freshly allocated ones.
+///
+/// Only targets that take bytes as a whole buffer, such as
+/// `serde_bytes::ByteBuf`, use the pool, as `Vec<u8>` is read element by
+/// element.
+pub fn from_rows_with_buffer_pool<'a, T>(input: Vec<Row>, pool: &mut BufferPool) -> Result<Vec<T>>
+    where T: Deserialize<'a>,
+{
+    input.into_iter().map(|row| {
+        let mut deserializer = Deserializer::from_row(row);
+        deserializer.buffer_pool = Some(mem::replace(pool, BufferPool::new()));
+        let value = T::deserialize(&mut deserializer);
+        if let Some(buffers) = deserializer.buffer_pool.take() {
+            *pool = buffers;
+        }
+        value
+    }).collect()
+}
+
+/// Attempt to deserialize from `Rows`, carrying on past rows that fail.
+///
+/// Returns every row that was deserialized, in order, along with the index
+/// and error of every row that wasn't, so bad rows can be set aside rather
+/// than failing the whole batch.
+pub fn from_rows_partial<'a, T>(input: Vec<Row>) -> (Vec<T>, Vec<(usize, Error)>)
+    where T: Deserialize<'a>,
+{
+    let mut values = Vec::with_capacity(input.len());
+    let mut errors = Vec::new();
+    for (index, row) in input.into_iter().enumerate() {
+        let mut deserializer = Deserializer::from_row(row);
+        match T::deserialize(&mut deserializer) {
+            Ok(value) => values.push(value),
+            Err(err) => errors.push((index, err)),
+        }
+    }
+
+    (values, errors)
+}
+
+/// A flag for aborting `from_rows_cancellable` from elsewhere, e.g. when a
+/// request's deadline passes. Clones share the same flag.
+#[derive(Clone, Debug, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    /// Create a token that isn't cancelled yet.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Cancel every deserialization watching this token.
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::SeqCst);
+    }
+
+    /// Whether the token has been cancelled.
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::SeqCst)
+    }
+}
+
+/// Attempt to deserialize from `Rows`, checking `token` before every row and
+/// failing with `Error::Cancelled` once it's cancelled.
+pub fn from_rows_cancellable<'a, T>(input: Vec<Row>, token: &CancellationToken) -> Result<Vec<T>>
+    where T: Deserialize<'a>,
+{
+    input.into_iter().map(|row| {
+        if token.is_cancelled() {
+            return Err(Error::Cancelled);
+        }
+
+        let mut deserializer = Deserializer::from_row(row);
+        T::deserialize(&mut deserializer)
+    }).collect()
+}
+
+/// Deserializes batches of rows, resolving which key each column is read
+/// under only once per column layout.
+///
+/// The resolved keys are cached together with the columns' names and types,
+/// and reused for every following batch with the same columns. A batch with
+/// different columns resolves them again and replaces the cache.
+pub struct CachedDeserializer<T> {
+    config: Config,
+    signature: Vec<(String, u32)>,
+    keys: Option<Arc<Vec<String>>>,
+    marker: PhantomData<fn() -> T>,
+}
+
+impl<T> CachedDeserializer<T> {
+    /// Create a `CachedDeserializer` with the default `Config`.
+    pub fn new() -> Self {
+        Self::with_config(Config::default())
+    }
+
+    /// Create a `CachedDeserializer` with the given `Config`.
+    pub fn with_config(config: Config) -> Self {
+        Self {
+            config,
+            signature: Vec::new(),
+            keys: None,
+            marker: PhantomData,
+        }
+    }
+
+    /// Whether the keys for the last seen column layout are cached.
+    pub fn is_cached(&self) -> bool {
+        self.keys.is_some()
+    }
+
+    /// Attempt to deserialize from `Rows`, reusing the cached keys if the
+    /// rows have the same columns as the previous batch.
+    pub fn from_rows<'a>(&mut self, input: Vec<Row>) -> Result<Vec<T>>
+        where T: Deserialize<'a>,
+    {
+        if let Some(row) = input.first() {
+            let matches = row.columns().len() == self.signature.len()
+                && row.columns().iter().zip(&self.signature).all(|(column, (name, oid))| {
+                    column.name() == name && column.type_().oid() == *oid
+                });
+
+            if !matches {
+                self.signature = row.columns().iter()
+                    .map(|column| (column.name().to_owned(), column.type_().oid()))
+                    .collect();
+                self.keys = None;
+            }
+        }
+
+        input.into_iter().map(|row| {
+            let mut deserializer = Deserializer::from_row_with_config(row, self.config.clone());
+            deserializer.keys = self.keys.clone();
+            let value = T::deserialize(&mut deserializer)?;
+            if self.keys.is_none() {
+                self.keys = Some(Arc::new(deserializer.resolved_keys()));
+            }
+            Ok(value)
+        }).collect()
+    }
+}
+
+impl<T> Default for CachedDeserializer<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+macro_rules! unsupported_type {
+    ($($fn_name:ident),*,) => {
+        $(
+            fn $fn_name<V: Visitor<'de>>(self, _: V) -> Result<V::Value> {
+                Err(Error::UnsupportedType)
+            }
+        )*
+    }
+}
+
+macro_rules! null_as_default {
+    ($this:ident, $v:ident, $fn_call:ident, $default:expr) => {
+        if $this.config.null_as_default && $this.is_null()? {
+            return $v.$fn_call($default);
+        }
+    }
+}
+
+macro_rules! get_value {
+    ($this:ident, $v:ident, $fn_call:ident, $ty:ty) => {{
+        // A JSON scalar, e.g. a number read into an integer or float.
+        #[cfg(feature = "json")]
+        {
+            if $this.is_json() {
+                return $this.json_value()?.deserialize_any($v)
+                    .map_err(|e| Error::InvalidType(e.to_string()));
+            }
+        }
+
+        $v.$fn_call($this.get::<$ty>()?)
+    }}
+}
+
+macro_rules! get_integer {
+    ($this:ident, $v:ident, $fn_call:ident, $ty:ty) => {{
+        if $this.coerce_integer() {
+            $this.visit_integer($v)
+        } else {
+            get_value!($this, $v, $fn_call, $ty)
+        }
+    }}
+}
+
+impl<'de, 'a: 'de, 'b> de::Deserializer<'de> for &'b mut Deserializer<'a> {
+    type Error = Error;
+
+    unsupported_type! {
+        deserialize_unit,
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        if self.is_null()? {
+            visitor.visit_none()
+        } else {
+            visitor.visit_some(self)
+        }
+    }
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        // The whole row, e.g. for an internally tagged enum, which finds its
+        // tag among the columns.
+        if !self.in_column {
+            return self.deserialize_map(visitor);
+        }
+
+        if self.is_null()? {
+            return visitor.visit_none();
+        }
+
+        #[cfg(feature = "json")]
+        {
+            if self.is_json() {
+                return self.json_value()?.deserialize_any(visitor)
+                    .map_err(|e| Error::InvalidType(e.to_string()));
+            }
+        }
+
+        match self.column_type() {
+            Some(&Type::BOOL) => get_value!(self, visitor, visit_bool, bool),
+            Some(&Type::INT2) => get_value!(self, visitor, visit_i16, i16),
+            Some(&Type::INT4) => get_value!(self, visitor, visit_i32, i32),
+            Some(&Type::INT8) => get_value!(self, visitor, visit_i64, i64),
+            Some(&Type::OID) => get_value!(self, visitor, visit_u32, u32),
+            Some(&Type::FLOAT4) => get_value!(self, visitor, visit_f32, f32),
+            Some(&Type::FLOAT8) => get_value!(self, visitor, visit_f64, f64),
+            Some(&Type::TEXT) | Some(&Type::VARCHAR) | Some(&Type::BPCHAR)
+                | Some(&Type::NAME) => get_value!(self, visitor, visit_string, String),
+            Some(&Type::BYTEA) => get_value!(self, visitor, visit_byte_buf, Vec<u8>),
+            Some(&Type::INET) | Some(&Type::CIDR) => self.deserialize_string(visitor),
+            Some(&Type::MONEY) => self.deserialize_i64(visitor),
+            #[cfg(feature = "with-uuid")]
+            Some(&Type::UUID) => self.deserialize_string(visitor),
+            #[cfg(feature = "macaddr")]
+            Some(&Type::MACADDR) => self.deserialize_string(visitor),
+            #[cfg(feature = "bit-vec")]
+            Some(&Type::BIT) | Some(&Type::VARBIT) => self.deserialize_seq(visitor),
+            // The exact text of a `numeric`, which `Decimal` parses.
+            #[cfg(feature = "with-rust-decimal")]
+            Some(&Type::NUMERIC) => visitor.visit_string(Numeric::from_sql(self.raw()?)?.to_string()),
+            #[cfg(feature = "with-chrono")]
+            Some(&Type::TIMESTAMPTZ) | Some(&Type::TIMESTAMP) | Some(&Type::DATE)
+                | Some(&Type::TIME) => self.deserialize_string(visitor),
+            #[cfg(feature = "hstore")]
+            Some(ty) if hstore::is_hstore(ty) => self.deserialize_map(visitor),
+            Some(ty) if range::member(ty).is_some() => self.deserialize_map(visitor),
+            _ => match self.fallback() {
+                UnknownFallback::Text => visitor.visit_str(self.raw_text()?),
+                UnknownFallback::Bytes => visitor.visit_bytes(self.raw()?),
+                UnknownFallback::Error => Err(Error::UnsupportedColumnType(
+                    self.column_type().map_or_else(String::new, |ty| ty.name().to_owned()))),
+            },
+        }
+    }
+
+    fn deserialize_ignored_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        if self.config.strict && self.in_column {
+            let name = self.input.columns().get(self.index).map_or("", |c| c.name());
+            return Err(Error::Message(format!("unexpected column `{}`", name)));
+        }
+
+        visitor.visit_unit()
+    }
+
+    fn deserialize_bool<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        null_as_default!(self, visitor, visit_bool, false);
+
+        get_value!(self, visitor, visit_bool, bool)
+    }
+
+    fn deserialize_i8<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        null_as_default!(self, visitor, visit_i8, 0);
+
+        get_integer!(self, visitor, visit_i8, i8)
+    }
+
+    fn deserialize_i16<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        null_as_default!(self, visitor, visit_i16, 0);
+
+        get_integer!(self, visitor, visit_i16, i16)
+    }
+
+    fn deserialize_i32<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        null_as_default!(self, visitor, visit_i32, 0);
+
+        get_integer!(self, visitor, visit_i32, i32)
+    }
+
+    fn deserialize_i64<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        null_as_default!(self, visitor, visit_i64, 0);
+
+        // Smaller integers always fit, so they're widened.
+        match self.column_type() {
+            Some(&Type::INT2) => return visitor.visit_i64(i64::from(self.get::<i16>()?)),
+            Some(&Type::INT4) => return visitor.visit_i64(i64::from(self.get::<i32>()?)),
+            // An amount in the currency's minor unit, e.g. cents.
+            Some(&Type::MONEY) => return visitor.visit_i64(Money::from_sql(self.raw()?)?.0),
+            _ => {}
+        }
+
+        get_integer!(self, visitor, visit_i64, i64)
+    }
+
+    fn deserialize_u8<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        null_as_default!(self, visitor, visit_u8, 0);
+
+        // A `"char"`'s single byte.
+        if let Some(&Type::CHAR) = self.column_type() {
+            return visitor.visit_u8(self.get::<i8>()? as u8);
+        }
+
+        visitor.visit_u8(self.unsigned("value out of range")?)
+    }
+
+    fn deserialize_u16<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        null_as_default!(self, visitor, visit_u16, 0);
+
+        visitor.visit_u16(self.unsigned("value out of range")?)
+    }
+
+    fn deserialize_u32<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        null_as_default!(self, visitor, visit_u32, 0);
+
+        // An `oid`, which is the one unsigned type Postgres has.
+        if let Some(&Type::OID) = self.column_type() {
+            return visitor.visit_u32(self.get::<u32>()?);
+        }
+
+        get_integer!(self, visitor, visit_u32, u32)
+    }
+
+    fn deserialize_u64<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        null_as_default!(self, visitor, visit_u64, 0);
+
+        // A `pg_lsn`'s position in the write-ahead log.
+        if let Some(&Type::PG_LSN) = self.column_type() {
+            return visitor.visit_u64(Lsn::from_sql(self.raw()?)?.0);
+        }
+
+        // Every `bigint` fits a `u64` unless it's negative.
+        visitor.visit_u64(self.unsigned("value is negative, so it can't be a u64")?)
+    }
+
+    fn deserialize_u128<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        null_as_default!(self, visitor, visit_u128, 0);
+
+        // A `uuid`'s 16 bytes, read as one big-endian integer.
+        if let Some(&Type::UUID) = self.column_type() {
+            let raw = self.raw()?;
+            if raw.len() != 16 {
+                return Err(Error::InvalidType(String::from("invalid uuid value")));
+            }
+
+            let mut bytes = [0; 16];
+            bytes.copy_from_slice(raw);
+            return visitor.visit_u128(u128::from_be_bytes(bytes));
+        }
+
+        // A whole number too large for a `bigint`.
+        if let Some(&Type::NUMERIC) = self.column_type() {
+            return visitor.visit_u128(Numeric::from_sql(self.raw()?)?.to_u128()?);
+        }
+
+        Err(Error::UnsupportedType)
+    }
+
+    fn deserialize_i128<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        null_as_default!(self, visitor, visit_i128, 0);
+
+        match self.column_type() {
+            Some(&Type::NUMERIC) => visitor.visit_i128(Numeric::from_sql(self.raw()?)?.to_i128()?),
+            Some(ty) if raw::is_integer(ty) =>
+                visitor.visit_i128(i128::from(raw::integer(ty, self.raw_nullable()?)?)),
+            _ => Err(Error::UnsupportedType),
+        }
+    }
+
+    fn deserialize_f32<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        null_as_default!(self, visitor, visit_f32, 0.0);
+
+        #[cfg(feature = "json")]
+        {
+            if self.is_json() {
+                return self.json_float(visitor);
+            }
+        }
+
+        get_value!(self, visitor, visit_f32, f32)
+    }
+
+    fn deserialize_f64<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        null_as_default!(self, visitor, visit_f64, 0.0);
+
+        #[cfg(feature = "json")]
+        {
+            if self.is_json() {
+                return self.json_float(visitor);
+            }
+        }
+
+        if self.config.coerce_money_to_f64 {
+            if let Some(&Type::MONEY) = self.column_type() {
+                return visitor.visit_f64(Money::from_sql(self.raw()?)?.to_f64());
+            }
+        }
+
+        // A `real` always fits, so it's widened.
+        if let Some(&Type::FLOAT4) = self.column_type() {
+            return visitor.visit_f64(f64::from(self.get::<f32>()?));
+        }
+
+        get_value!(self, visitor, visit_f64, f64)
+    }
+
+    fn deserialize_char<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        null_as_default!(self, visitor, visit_char, '\0');
+
+        #[cfg(feature = "json")]
+        {
+            if self.is_json() {
+                return self.json_value()?.deserialize_char(visitor)
+                    .map_err(|e| Error::InvalidType(e.to_string()));
+            }
+        }
+
+        let text = self.get::<&str>()?;
+        let mut chars = text.chars();
+        match (chars.next(), chars.next()) {
+            (Some(c), None) => visitor.visit_char(c),
+            _ => Err(Error::InvalidType(format!(
+                "expected a single character, found {:?}", text))),
+        }
+    }
+
+    fn deserialize_str<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        null_as_default!(self, visitor, visit_str, "");
+
+        if self.column_type().map_or(false, <&str as FromSql>::accepts) {
+            if let Some(text) = self.borrowed::<&'a str>() {
+                return visitor.visit_borrowed_str(text?);
+            }
+        }
+
+        self.deserialize_string(visitor)
+    }
+
+    fn deserialize_string<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        null_as_default!(self, visitor, visit_str, "");
+
+        if let Some(network) = self.network()? {
+            return visitor.visit_string(network.to_string());
+        }
+
+        if let Some(&Type::MONEY) = self.column_type() {
+            return visitor.visit_string(Money::from_sql(self.raw()?)?.to_string());
+        }
+
+        if let Some(&Type::PG_LSN) = self.column_type() {
+            return visitor.visit_string(Lsn::from_sql(self.raw()?)?.to_string());
+        }
+
+        if let Some(&Type::NUMERIC) = self.column_type() {
+            return visitor.visit_string(Numeric::from_sql(self.raw()?)?.to_string());
+        }
+
+        // `Uuid` reads its 16 bytes as well as its text form.
+        #[cfg(feature = "with-uuid")]
+        {
+            if let Some(&Type::UUID) = self.column_type() {
+                return visitor.visit_bytes(self.get::<::uuid::Uuid>()?.as_bytes());
+            }
+        }
+
+        // A MAC address in the text form `MacAddress` parses.
+        #[cfg(feature = "macaddr")]
+        {
+            if let Some(&Type::MACADDR) = self.column_type() {
+                return visitor.visit_string(self.get::<::eui48::MacAddress>()?.to_hex_string());
+            }
+        }
+
+        if self.config.geometry_as_wkt {
+            if let Some(ty) = self.column_type() {
+                if let Some(wkt) = wkt::from_sql(ty, self.raw()?)? {
+                    return visitor.visit_string(wkt);
+                }
+            }
+        }
+
+        #[cfg(feature = "with-chrono")]
+        {
+            if let Some(ty) = self.column_type() {
+                if let Some(text) = timestamp::to_text(ty, self.raw_nullable()?)? {
+                    return visitor.visit_string(text);
+                }
+            }
+        }
+
+        // An ISO 8601 duration, which `iso8601::Duration` is read from.
+        #[cfg(feature = "with-iso8601")]
+        {
+            if let Some(&Type::INTERVAL) = self.column_type() {
+                return visitor.visit_string(Interval::from_sql(self.raw()?)?.to_iso8601()?);
+            }
+        }
+
+        match self.fallback() {
+            UnknownFallback::Text => visitor.visit_str(self.raw_text()?),
+            UnknownFallback::Bytes => visitor.visit_bytes(self.raw()?),
+            UnknownFallback::Error => get_value!(self, visitor, visit_string, String),
+        }
+    }
+
+    fn deserialize_bytes<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        // The undecoded binary value, e.g. an array's to be read with
+        // `RawArray`, borrowed from the row when possible.
+        if let Some(raw) = self.borrowed::<Raw<'a>>() {
+            return visitor.visit_borrowed_bytes(raw?.0);
+        }
+
+        visitor.visit_bytes(self.raw()?)
+    }
+
+    fn deserialize_byte_buf<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        match self.fallback() {
+            UnknownFallback::Text => visitor.visit_str(self.raw_text()?),
+            UnknownFallback::Bytes => visitor.visit_bytes(self.raw()?),
+            UnknownFallback::Error => {
+                if let Some(mut pool) = self.buffer_pool.take() {
+                    let buffer = self.get::<&[u8]>().map(|raw| {
+                        let mut buffer = pool.take(raw.len());
+                        buffer.extend_from_slice(raw);
+                        buffer
+                    });
+                    self.buffer_pool = Some(pool);
+                    return visitor.visit_byte_buf(buffer?);
+                }
+
+                get_value!(self, visitor, visit_byte_buf, Vec<u8>)
+            }
+        }
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        // A JSON array stored in a single column, as opposed to `jsonb[]`.
+        #[cfg(feature = "json")]
+        {
+            if self.is_json() {
+                return self.json_value()?.deserialize_seq(visitor)
+                    .map_err(|e| Error::InvalidType(e.to_string()));
+            }
+        }
+
+        if self.fallback() != UnknownFallback::Error {
+            return visitor.visit_seq(SeqDeserializer::new(self.raw()?.iter().cloned()));
+        }
+
+        if let Some(&Kind::Array(ref member)) = self.column_type().map(|ty| ty.kind()) {
+            return array::deserialize(member, self.raw()?, &self.config, visitor);
+        }
+
+        // A bit string's bits, e.g. into a `Vec<bool>`.
+        #[cfg(feature = "bit-vec")]
+        {
+            if self.column_type().map_or(false, bits::is_bits) {
+                return bits::deserialize_seq(self.get()?, visitor);
+            }
+        }
+
+        let raw = self.get::<&[u8]>()?;
+
+        visitor.visit_seq(SeqDeserializer::new(raw.iter().cloned()))
+    }
+
+
+    fn deserialize_enum<V: Visitor<'de>>(self,
+                                         _name: &'static str,
+                                         variants: &'static [&'static str],
+                                         visitor: V)
+        -> Result<V::Value>
+    {
+        // Internally tagged enums go through `deserialize_any`, the other
+        // representations end up here.
+        #[cfg(feature = "json")]
+        {
+            if self.is_json() {
+                return self.json_value()?.deserialize_enum(_name, variants, visitor)
+                    .map_err(|e| Error::InvalidType(e.to_string()));
+            }
+        }
+
+        // A unit variant named by the column's label, from either a Postgres
+        // enum or text, both sent as the label's UTF-8.
+        let ty = self.column_type().ok_or_else(|| self.column_not_found())?;
+        let is_label = match (ty, ty.kind()) {
+            (&Type::TEXT, _) | (&Type::VARCHAR, _) | (&Type::BPCHAR, _) | (&Type::NAME, _) => true,
+            (_, &Kind::Enum(_)) => true,
+            _ => self.fallback() == UnknownFallback::Text,
+        };
+        if !is_label {
+            return Err(Error::InvalidType(format!("expected text or enum column, found {}", ty.name())));
+        }
+        if self.is_null()? {
+            return Err(Error::InvalidType(String::from("expected text or enum column, found NULL")));
+        }
+
+        let label = self.config.enum_label(self.raw_text()?, variants);
+        visitor.visit_enum(UnitVariant(label))
+    }
+
+    fn deserialize_identifier<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        // Outside of a column, the current column's name, as a key would be.
+        if !self.in_column {
+            let column = self.input.columns().get(self.index).ok_or_else(|| self.column_not_found())?;
+            return visitor.visit_str(&self.config.column_case.apply(column.name()));
+        }
+
+        // A variant's name, e.g. from an internally tagged enum's tag column,
+        // read from either a Postgres enum or text.
+        visitor.visit_str(self.raw_text()?)
+    }
+
+    fn deserialize_unit_struct<V: Visitor<'de>>(self, _: &str, _: V)
+        -> Result<V::Value>
+    {
+        Err(Error::UnsupportedType)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(self, name: &'static str, visitor: V)
+        -> Result<V::Value>
+    {
+        if name == typed::TYPED {
+            let pg_type = self.column_type().ok_or_else(|| self.column_not_found())?.name().to_owned();
+            return visitor.visit_seq(TypedAccess::new(pg_type, self));
+        }
+
+        if name == epoch::UNIX_MICROS {
+            if let Some(ty) = self.column_type() {
+                if let Some(micros) = epoch::to_unix_micros(ty, self.raw()?)? {
+                    return visitor.visit_i64(micros);
+                }
+            }
+        }
+
+        if name == interval::DURATION {
+            if let Some(&Type::INTERVAL) = self.column_type() {
+                return visitor.visit_i64(Interval::from_sql(self.raw()?)?.to_microseconds()?);
+            }
+        }
+
+        #[cfg(feature = "with-geo-types")]
+        {
+            if self.in_column {
+                if let Some(geometry) = self.geometry()? {
+                    return geometry.deserialize_newtype_struct(name, visitor);
+                }
+            }
+        }
+
+        // Any other newtype, e.g. `struct UserId(i32)`, is read as what it
+        // wraps.
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_tuple<V: Visitor<'de>>(self, len: usize, visitor: V)
+        -> Result<V::Value>
+    {
+        // A tuple for the whole row takes the columns by position.
+        if !self.in_column {
+            let columns = self.input.columns().len() - self.index;
+            if columns != len {
+                return Err(de::Error::invalid_length(columns, &visitor));
+            }
+
+            return visitor.visit_seq(self);
+        }
+
+        match self.column_type() {
+            // An EUI-64 address, read as its eight raw bytes.
+            #[cfg(feature = "macaddr")]
+            Some(&Type::MACADDR8) => self.visit_byte_array(len, visitor),
+            // A `bytea` of a fixed length, e.g. into a `[u8; 16]` hash.
+            Some(&Type::BYTEA) => self.visit_byte_array(len, visitor),
+            // A `point`'s coordinates, as `(x, y)`.
+            Some(&Type::POINT) if len == 2 => {
+                let (x, y) = wkt::point_from_sql(self.raw()?)?;
+                visitor.visit_seq(SeqDeserializer::new(vec![x, y].into_iter()))
+            }
+            // The exact value of a `numeric`, as a `BigRational`.
+            #[cfg(feature = "with-num-rational")]
+            Some(&Type::NUMERIC) => {
+                let numeric = Numeric::from_sql(self.raw()?)?;
+                Rational::Ratio(numeric.to_rational()).deserialize_tuple(len, visitor)
+            }
+            _ => Err(Error::UnsupportedType),
+        }
+    }
+
+    fn deserialize_tuple_struct<V: Visitor<'de>>(self,
+                                                 _: &str,
+                                                 len: usize,
+                                                 visitor: V)
+        -> Result<V::Value>
+    {
+        if !self.in_column {
+            return self.deserialize_tuple(len, visitor);
+        }
+
+        Err(Error::UnsupportedType)
+    }
+
+    fn deserialize_map<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        #[cfg(feature = "json")]
+        {
+            if self.in_column && self.is_json() {
+                return self.json_value()?.deserialize_map(visitor)
+                    .map_err(|e| Error::InvalidType(e.to_string()));
+            }
+        }
+
+        // A range's bounds and whether they're inclusive.
+        if self.in_column {
+            if let Some(member) = self.column_type().and_then(range::member) {
+                return range::deserialize(member, self.raw()?, &self.config, visitor);
+            }
+        }
+
+        // An `hstore`'s entries, rather than the row's columns.
+        #[cfg(feature = "hstore")]
+        {
+            if self.in_column && self.column_type().map_or(false, hstore::is_hstore) {
+                return hstore::deserialize(self.get()?, visitor);
+            }
+        }
+
+        // A map of column name to value, e.g. a `BTreeMap<String, i32>`.
+        visitor.visit_map(self)
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(self, name: &'static str, fields: &'static [&'static str], v: V) -> Result<V::Value> {
+        #[cfg(feature = "with-geo-types")]
+        {
+            if self.in_column {
+                if let Some(geometry) = self.geometry()? {
+                    return geometry.deserialize_struct(name, fields, v);
+                }
+            }
+        }
+
+        // A JSON object stored in a single column, nested objects included.
+        #[cfg(feature = "json")]
+        {
+            if self.in_column && self.is_json() {
+                return self.json_value()?.deserialize_struct(name, fields, v)
+                    .map_err(|e| Error::InvalidType(e.to_string()));
+            }
+        }
+
+        if self.in_column {
+            if let Some(member) = self.column_type().and_then(range::member) {
+                return range::deserialize(member, self.raw()?, &self.config, v);
+            }
+        }
+
+        // A `point`'s coordinates, as a struct with `x` and `y` fields.
+        if self.in_column {
+            if let Some(&Type::POINT) = self.column_type() {
+                let (x, y) = wkt::point_from_sql(self.raw()?)?;
+                return v.visit_map(MapDeserializer::new(vec![("x", x), ("y", y)].into_iter()));
+            }
+        }
+
+        // An `interval` into a `std::time::Duration`, read from its seconds
+        // and nanoseconds.
+        if self.in_column && name == "Duration" && fields == ["secs", "nanos"] {
+            if let Some(&Type::INTERVAL) = self.column_type() {
+                let duration = Interval::from_sql(self.raw()?)?.to_std()?;
+                let parts = vec![duration.as_secs(), u64::from(duration.subsec_nanos())];
+                return v.visit_seq(SeqDeserializer::new(parts.into_iter()));
+            }
+        }
+
+        // A bit string, into a `BitVec`.
+        #[cfg(feature = "bit-vec")]
+        {
+            if self.in_column && self.column_type().map_or(false, bits::is_bits) {
+                return bits::deserialize_struct(self.get()?, v);
+            }
+        }
+
+        if self.ordered {
+            // Running out of columns before fields is the only way for the
+            // struct to be short of elements.
+            return v.visit_seq(&mut *self).map_err(|err| match err {
+                Error::Message(ref msg) if msg.starts_with("invalid length ") => {
+                    let index = self.input.columns().len();
+                    match fields.get(index) {
+                        Some(field) => Error::ColumnNotFound { name: field.to_string(), index },
+                        None => err.clone(),
+                    }
+                }
+                err => err,
+            });
+        }
+
+        self.whole_row = fields.contains(&WHOLE_ROW);
+        if let Some(ref mapper) = self.field_mapper {
+            self.fields = fields.iter().map(|field| (mapper(field), *field)).collect();
+        }
+        for field in fields {
+            if let Some(column) = self.overrides.get(*field) {
+                self.fields.insert(column.clone(), *field);
+            }
+        }
+
+        let result = v.visit_map(&mut *self);
+        result.map_err(|err| self.suggest(err, fields))
+    }
+}
+
+/// Deserializes every column of the row as one map, for the `*` field.
+struct WholeRow<'b, 'a: 'b>(&'b mut Deserializer<'a>);
+
+impl<'de, 'a: 'de, 'b> de::Deserializer<'de> for WholeRow<'b, 'a> {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_map(self.0)
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_some(self)
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 u8 u16 u32 u64 f32 f64 char str string bytes
+        byte_buf unit unit_struct newtype_struct seq tuple tuple_struct map
+        struct enum identifier ignored_any
+    }
+}
+
+impl<'de, 'a: 'de> de::MapAccess<'de> for Deserializer<'a> {
+    type Error = Error;
+
+    fn next_key_seed<T: de::DeserializeSeed<'de>>(&mut self, seed: T)
+        -> Result<Option<T::Value>>
+    {
+        while self.index < self.input.columns().len() && self.is_shadowed(self.index) {
+            self.index += 1;
+        }
+
+        if self.index >= self.input.columns().len() {
+            if self.whole_row {
+                self.whole_row = false;
+                return seed.deserialize(WHOLE_ROW.into_deserializer()).map(Some);
+            }
+
+            return Ok(None)
+        }
+
+        if let Some(ref keys) = self.keys {
+            let key = keys.get(self.index).ok_or_else(|| self.column_not_found())?;
+            return seed.deserialize(key.as_str().into_deserializer()).map(Some);
+        }
+
+        let name = self.input.columns()
+            .get(self.index)
+            .ok_or_else(|| self.column_not_found())?
+            .name();
+        let name = self.config.column_case.apply(name);
+        let key = self.fields.get(&*name).map_or(&*name, |field| *field);
+
+        seed.deserialize(key.into_deserializer()).map(Some)
+    }
+
+    fn next_value_seed<T: de::DeserializeSeed<'de>>(&mut self, seed: T)
+        -> Result<T::Value>
+    {
+        // Past the last column only the whole row is left to hand out.
+        let len = self.input.columns().len();
+        if self.index >= len {
+            self.index = 0;
+            let result = seed.deserialize(WholeRow(&mut *self));
+            self.index = len;
+            return result;
+        }
+
+        self.next_column(seed)
+    }
+}
+
+impl<'de, 'a: 'de> de::SeqAccess<'de> for Deserializer<'a> {
+    type Error = Error;
+
+    fn next_element_seed<T: de::DeserializeSeed<'de>>(&mut self, seed: T)
+        -> Result<Option<T::Value>>
+    {
+        if self.index >= self.input.columns().len() {
+            return Ok(None)
+        }
+
+        self.next_column(seed).map(Some)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.input.columns().len().saturating_sub(self.index))
+    }
+}
+
+/// A Postgres enum or text label, read as a unit variant of a Rust enum.
+pub(crate) struct UnitVariant<'a>(pub(crate) &'a str);
+
+impl<'de, 'a> de::EnumAccess<'de> for UnitVariant<'a> {
+    type Error = Error;
+    type Variant = Self;
+
+    fn variant_seed<V: de::DeserializeSeed<'de>>(self, seed: V)
+        -> Result<(V::Value, Self::Variant)>
+    {
+        let value = seed.deserialize(IntoDeserializer::<Error>::into_deserializer(self.0))?;
+        Ok((value, self))
+    }
+}
+
+impl<'de, 'a> de::VariantAccess<'de> for UnitVariant<'a> {
+    type Error = Error;
+
+    fn unit_variant(self) -> Result<()> {
+        Ok(())
+    }
+
+    fn newtype_variant_seed<T: de::DeserializeSeed<'de>>(self, _: T)
+        -> Result<T::Value>
+    {
+        Err(Error::UnsupportedType)
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(self, _: usize, _: V)
+        -> Result<V::Value>
+    {
+        Err(Error::UnsupportedType)
+    }
+
+    fn struct_variant<V: Visitor<'de>>(self, _: &'static [&'static str], _: V)
+        -> Result<V::Value>
+    {
+        Err(Error::UnsupportedType)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+
+    use serde_derive::Deserialize;
+
+    use postgres::Connection;
+
+    fn setup_and_connect_to_db() -> Connection {
+        let user = env::var("PGUSER").unwrap_or("postgres".into());
+        let pass = env::var("PGPASSWORD").map(|p| format!("{}", p)).unwrap_or("postgres".into());
+        let addr = env::var("PGADDR").unwrap_or("localhost".into());
+        let port = env::var("PGPORT").unwrap_or("5432".into());
+        let url = format!("postgres://{user}:{pass}@{addr}:{port}", user = user, pass = pass, addr = addr, port = port);
+        Connection::connect(url, postgres::TlsMode::None).unwrap()
+    }
+
+    #[test]
+    fn non_null() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Buu {
+            wants_candy: bool,
+            width: i16,
+            amount_eaten: i32,
+            amount_want_to_eat: i64,
+            speed: f32,
+            weight: f64,
+            catchphrase: String,
+            stomach_contents: Vec<u8>,
+        }
+
+        let connection = setup_and_connect_to_db();
+
+        connection.execute("CREATE TABLE IF NOT EXISTS Buu (
+                    wants_candy BOOL NOT NULL,
+                    width SMALLINT NOT NULL,
+                    amount_eaten INT NOT NULL,
+                    amount_want_to_eat BIGINT NOT NULL,
+                    speed REAL NOT NULL,
+                    weight DOUBLE PRECISION NOT NULL,
+                    catchphrase VARCHAR NOT NULL,
+                    stomach_contents BYTEA NOT NULL
+        )", &[]).unwrap();
+
+        connection.execute("INSERT INTO Buu (
+            wants_candy,
+            width,
+            amount_eaten,
+            amount_want_to_eat,
+            speed,
+            weight,
+            catchphrase,
+            stomach_contents
+        ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8)",
+        &[&true, &20i16, &1000i32, &1000_000i64, &99.99f32, &9999.9999f64, &String::from("Woo Woo"), &vec![1u8, 2, 3, 4, 5, 6]]).unwrap();
+
+        let results = connection.query("SELECT wants_candy,
+            width,
+            amount_eaten,
+            amount_want_to_eat,
+            speed,
+            weight,
+            catchphrase,
+            stomach_contents
+ FROM Buu", &[]).unwrap();
+
+        let row = results.get(0);
+
+        let buu: Buu = super::from_row(row).unwrap();
+
+        assert_eq!(true, buu.wants_candy);
+        assert_eq!(20, buu.width);
+        assert_eq!(1000, buu.amount_eaten);
+        assert_eq!(1000_000, buu.amount_want_to_eat);
+        assert_eq!(99.99, buu.speed);
+        assert_eq!(9999.9999, buu.weight);
+        assert_eq!("Woo Woo", buu.catchphrase);
+        assert_eq!(vec![1,2,3,4,5,6], buu.stomach_contents);
+
+        connection.execute("DROP TABLE Buu", &[]).unwrap();
+    }
+
+    #[test]
+    fn nullable() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Buu {
+            wants_candy: Option<bool>,
+            width: Option<i16>,
+            amount_eaten: Option<i32>,
+            amount_want_to_eat: Option<i64>,
+            speed: Option<f32>,
+            weight: Option<f64>,
+            catchphrase: Option<String>,
+            stomach_contents: Option<Vec<u8>>,
+        }
+
+        let connection = setup_and_connect_to_db();
+
+        connection.execute("CREATE TABLE IF NOT EXISTS NullBuu (
+                    wants_candy BOOL,
+                    width SMALLINT,
+                    amount_eaten INT,
+                    amount_want_to_eat BIGINT,
+                    speed REAL,
+                    weight DOUBLE PRECISION,
+                    catchphrase VARCHAR,
+                    stomach_contents BYTEA
+        )", &[]).unwrap();
+
+        connection.execute("INSERT INTO NullBuu (
+            wants_candy,
+            width,
+            amount_eaten,
+            amount_want_to_eat,
+            speed,
             weight,
             catchphrase,
             stomach_contents
@@ -370,135 +1934,3058 @@ mod tests {
             NULL)",
         &[]).unwrap();
 
-        let results = connection.query("SELECT wants_candy,
-            width,
-            amount_eaten,
-            amount_want_to_eat,
-            speed,
-            weight,
-            catchphrase,
-            stomach_contents
- FROM NullBuu", &[]).unwrap();
+        let results = connection.query("SELECT wants_candy,
+            width,
+            amount_eaten,
+            amount_want_to_eat,
+            speed,
+            weight,
+            catchphrase,
+            stomach_contents
+ FROM NullBuu", &[]).unwrap();
+
+        let row = results.get(0);
+
+        let buu: Buu = super::from_row(row).unwrap();
+
+        assert_eq!(None, buu.wants_candy);
+        assert_eq!(None, buu.width);
+        assert_eq!(None, buu.amount_eaten);
+        assert_eq!(None, buu.amount_want_to_eat);
+        assert_eq!(None, buu.speed);
+        assert_eq!(None, buu.weight);
+        assert_eq!(None, buu.catchphrase);
+        assert_eq!(None, buu.stomach_contents);
+
+        connection.execute("DROP TABLE NullBuu", &[]).unwrap();
+    }
+
+    #[test]
+    fn mispelled_field_name() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Buu {
+            wants_candie: bool,
+        }
+
+        let connection = setup_and_connect_to_db();
+
+        connection.execute("CREATE TABLE IF NOT EXISTS SpellBuu (
+                    wants_candy BOOL NOT NULL
+        )", &[]).unwrap();
+
+        connection.execute("INSERT INTO SpellBuu (
+            wants_candy
+        ) VALUES ($1)",
+        &[&true]).unwrap();
+
+        let results = connection.query("SELECT wants_candy FROM SpellBuu", &[]).unwrap();
+
+        let row = results.get(0);
+
+        assert_eq!(
+            super::from_row::<Buu>(row),
+            Err(super::Error::Message(String::from(
+                "missing field `wants_candie`; did you mean `wants_candy`?"))));
+
+        connection.execute("DROP TABLE SpellBuu", &[]).unwrap();
+    }
+
+    #[test]
+    fn missing_optional() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Buu {
+            wants_candy: bool,
+        }
+
+        let connection = setup_and_connect_to_db();
+
+        connection.execute("CREATE TABLE IF NOT EXISTS MiBuu (
+                    wants_candy BOOL
+        )", &[]).unwrap();
+
+        connection.execute("INSERT INTO MiBuu (
+            wants_candy
+        ) VALUES ($1)",
+        &[&None::<bool>]).unwrap();
+
+        let results = connection.query("SELECT wants_candy FROM MiBuu", &[]).unwrap();
+
+        let row = results.get(0);
+
+        match super::from_row::<Buu>(row) {
+            Err(ref err @ super::Error::Column { .. }) => {
+                use std::error::Error;
+
+                assert!(err.to_string().starts_with("wants_candy: "));
+                assert!(err.source().is_some());
+            }
+            other => panic!("expected a column error, found {:?}", other),
+        }
+
+        connection.execute("DROP TABLE MiBuu", &[]).unwrap();
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn json_array() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Item {
+            id: i32,
+        }
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Basket {
+            items: Vec<Item>,
+        }
+
+        let connection = setup_and_connect_to_db();
+
+        let results = connection.query(
+            r#"SELECT '[{"id":1},{"id":2}]'::jsonb AS items"#, &[]).unwrap();
+
+        let row = results.get(0);
+
+        let basket: Basket = super::from_row(row).unwrap();
+
+        assert_eq!(vec![Item { id: 1 }, Item { id: 2 }], basket.items);
+    }
+
+    #[test]
+    fn metrics_per_row() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Buu {
+            id: i32,
+        }
+
+        let connection = setup_and_connect_to_db();
+
+        let results = connection.query(
+            "SELECT generate_series(1, 3) AS id", &[]).unwrap();
+
+        let mut seen = Vec::new();
+        let buus: Vec<Buu> = super::from_rows_with_metrics(results, |index, _| {
+            seen.push(index);
+        }).unwrap();
+
+        assert_eq!(3, buus.len());
+        assert_eq!(vec![0, 1, 2], seen);
+    }
+
+    #[cfg(feature = "macaddr")]
+    #[test]
+    fn macaddr8() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Device {
+            mac: [u8; 8],
+        }
+
+        let connection = setup_and_connect_to_db();
+
+        let results = connection.query(
+            "SELECT '08:00:2b:01:02:03:04:05'::macaddr8 AS mac", &[]).unwrap();
+
+        let row = results.get(0);
+
+        let device: Device = super::from_row(row).unwrap();
+
+        assert_eq!([0x08, 0x00, 0x2b, 0x01, 0x02, 0x03, 0x04, 0x05], device.mac);
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn whole_row_json() {
+        #[derive(Debug, Deserialize)]
+        struct Audit {
+            id: i32,
+            name: String,
+            #[serde(rename = "*")]
+            raw: ::serde_json::Value,
+        }
+
+        let connection = setup_and_connect_to_db();
+
+        let results = connection.query(
+            "SELECT 1 AS id, 'Goku'::text AS name", &[]).unwrap();
+
+        let row = results.get(0);
+
+        let audit: Audit = super::from_row(row).unwrap();
+
+        assert_eq!(1, audit.id);
+        assert_eq!("Goku", audit.name);
+        assert_eq!(
+            ::serde_json::from_str::<::serde_json::Value>(r#"{"id":1,"name":"Goku"}"#).unwrap(),
+            audit.raw);
+    }
+
+    #[test]
+    fn unknown_type_as_text() {
+        use config::{Config, UnknownFallback};
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Goku {
+            mood: String,
+        }
+
+        let connection = setup_and_connect_to_db();
+
+        connection.execute("CREATE TYPE saiyan_mood AS ENUM ('calm', 'angry')",
+        &[]).unwrap();
+
+        let results = connection.query("SELECT 'angry'::saiyan_mood AS mood", &[])
+            .unwrap();
+
+        let row = results.get(0);
+
+        let config = Config::new().unknown_type_fallback(UnknownFallback::Text);
+        let goku: Goku = super::from_row_with_config(row, config).unwrap();
+
+        assert_eq!("angry", goku.mood);
+
+        connection.execute("DROP TYPE saiyan_mood", &[]).unwrap();
+    }
+
+    #[test]
+    fn coerced_int_array() {
+        use config::Config;
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Buu {
+            sizes: Vec<i64>,
+        }
+
+        let connection = setup_and_connect_to_db();
+
+        let results = connection.query("SELECT ARRAY[1,2,3]::int2[] AS sizes", &[])
+            .unwrap();
+
+        let row = results.get(0);
+
+        let config = Config::new().coerce_integers(true);
+        let buu: Buu = super::from_row_with_config(row, config).unwrap();
+
+        assert_eq!(vec![1, 2, 3], buu.sizes);
+    }
+
+    #[test]
+    fn ordered_fields() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Buu {
+            catchphrase: String,
+            width: i16,
+        }
+
+        let connection = setup_and_connect_to_db();
+
+        let results = connection.query(
+            "SELECT 'Woo Woo'::text AS phrase, 20::int2 AS w", &[]).unwrap();
+
+        let row = results.get(0);
+
+        let buu: Buu = super::from_row_ordered(row).unwrap();
+
+        assert_eq!(Buu { catchphrase: String::from("Woo Woo"), width: 20 }, buu);
+    }
+
+    #[cfg(feature = "with-geo-types")]
+    #[test]
+    fn geo_types() {
+        use geo_types::{Coordinate, LineString, Point, Polygon};
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Map {
+            spawn: Point<f64>,
+            route: LineString<f64>,
+            area: Polygon<f64>,
+        }
+
+        let connection = setup_and_connect_to_db();
+
+        let results = connection.query("SELECT
+            '(1, 2)'::point AS spawn,
+            '[(0, 0), (1, 1), (2, 0)]'::path AS route,
+            '((0, 0), (0, 1), (1, 1))'::polygon AS area", &[]).unwrap();
+
+        let row = results.get(0);
+
+        let map: Map = super::from_row(row).unwrap();
+
+        let coordinate = |x, y| Coordinate { x, y };
+        assert_eq!(Point(coordinate(1.0, 2.0)), map.spawn);
+        assert_eq!(
+            LineString(vec![coordinate(0.0, 0.0), coordinate(1.0, 1.0), coordinate(2.0, 0.0)]),
+            map.route);
+        assert_eq!(
+            Polygon::new(
+                LineString(vec![coordinate(0.0, 0.0), coordinate(0.0, 1.0), coordinate(1.0, 1.0)]),
+                vec![]),
+            map.area);
+    }
+
+    #[cfg(feature = "with-geo-types")]
+    #[test]
+    fn geo_types_among_columns() {
+        use geo_types::{Coordinate, Point};
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Spawn {
+            id: i32,
+            location: Point<f64>,
+            name: String,
+        }
+
+        let connection = setup_and_connect_to_db();
+
+        let results = connection.query("SELECT 1::int4 AS id,
+            '(1, 2)'::point AS location,
+            'start'::text AS name", &[]).unwrap();
+
+        let row = results.get(0);
+
+        let spawn: Spawn = super::from_row(row).unwrap();
+
+        assert_eq!(Spawn {
+            id: 1,
+            location: Point(Coordinate { x: 1.0, y: 2.0 }),
+            name: String::from("start"),
+        }, spawn);
+    }
+
+    #[test]
+    fn field_mapper() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Buu {
+            width: i16,
+            catchphrase: String,
+        }
+
+        let connection = setup_and_connect_to_db();
+
+        let results = connection.query(
+            "SELECT 20::int2 AS buu_width, 'Woo Woo'::text AS buu_catchphrase",
+            &[]).unwrap();
+
+        let row = results.get(0);
+
+        let buu: Buu = super::from_row_with_field_mapper(row, |field| {
+            format!("buu_{}", field)
+        }).unwrap();
+
+        assert_eq!(Buu { width: 20, catchphrase: String::from("Woo Woo") }, buu);
+
+        // A column named after a field is ignored when the field is mapped
+        // to another one, and the prefix can be borrowed.
+        let results = connection.query(
+            "SELECT 30::int2 AS width, 20::int2 AS buu_width, 'Woo Woo'::text AS buu_catchphrase",
+            &[]).unwrap();
+
+        let row = results.get(0);
+
+        let prefix = String::from("buu");
+        let buu: Buu = super::from_row_with_field_mapper(row, |field| {
+            format!("{}_{}", prefix, field)
+        }).unwrap();
+
+        assert_eq!(Buu { width: 20, catchphrase: String::from("Woo Woo") }, buu);
+    }
+
+    #[test]
+    fn computed_bool() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Check {
+            #[serde(rename = "matches")]
+            is_match: bool,
+        }
+
+        let connection = setup_and_connect_to_db();
+
+        let results = connection.query(
+            r#"SELECT '{"a":1,"b":2}'::jsonb @> '{"a":1}' AS matches"#, &[]).unwrap();
+
+        let row = results.get(0);
+
+        let check: Check = super::from_row(row).unwrap();
+
+        assert_eq!(true, check.is_match);
+    }
+
+    #[cfg(feature = "with-num-rational")]
+    #[test]
+    fn numeric_rational() {
+        use num_bigint::BigInt;
+        use num_rational::BigRational;
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Share {
+            share: BigRational,
+        }
+
+        let connection = setup_and_connect_to_db();
+
+        let results = connection.query("SELECT 0.3333::numeric AS share", &[])
+            .unwrap();
+
+        let row = results.get(0);
+
+        let share: Share = super::from_row(row).unwrap();
+
+        assert_eq!(BigRational::new(BigInt::from(3333), BigInt::from(10000)), share.share);
+    }
+
+    #[test]
+    fn try_from_container() {
+        use std::convert::TryFrom;
+
+        #[derive(Debug, Deserialize)]
+        struct RawUser {
+            name: String,
+            age: i32,
+        }
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        #[serde(try_from = "RawUser")]
+        struct User {
+            name: String,
+            age: u8,
+        }
+
+        impl TryFrom<RawUser> for User {
+            type Error = String;
+
+            fn try_from(raw: RawUser) -> ::std::result::Result<Self, String> {
+                if raw.age < 0 {
+                    return Err(format!("invalid age {}", raw.age));
+                }
+
+                Ok(User { name: raw.name, age: raw.age as u8 })
+            }
+        }
+
+        let connection = setup_and_connect_to_db();
+
+        let results = connection.query(
+            "SELECT 'Jane'::text AS name, 23 AS age", &[]).unwrap();
+
+        let row = results.get(0);
+
+        assert_eq!(
+            super::from_row::<User>(row),
+            Ok(User { name: String::from("Jane"), age: 23 }));
+
+        let results = connection.query(
+            "SELECT 'Jane'::text AS name, -1 AS age", &[]).unwrap();
+
+        let row = results.get(0);
+
+        assert_eq!(
+            super::from_row::<User>(row),
+            Err(super::Error::Message(String::from("invalid age -1"))));
+    }
+
+    #[test]
+    fn has_columns() {
+        let connection = setup_and_connect_to_db();
+
+        let results = connection.query("SELECT 1 AS a, 2 AS b", &[]).unwrap();
+
+        let row = results.get(0);
+
+        assert!(super::row_has_columns(&row, &["a", "b"]));
+        assert!(super::row_has_columns(&row, &[]));
+        assert!(!super::row_has_columns(&row, &["a", "c"]));
+    }
+
+    #[test]
+    fn network_arrays() {
+        use std::net::IpAddr;
+
+        use ipnetwork::IpNetwork;
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Hosts {
+            addrs: Vec<IpAddr>,
+            networks: Vec<IpNetwork>,
+        }
+
+        let connection = setup_and_connect_to_db();
+
+        let results = connection.query("SELECT
+            ARRAY['192.168.1.1', '::1']::inet[] AS addrs,
+            ARRAY['10.0.0.0/8', '2001:db8::/32']::cidr[] AS networks", &[]).unwrap();
+
+        let row = results.get(0);
+
+        let hosts: Hosts = super::from_row(row).unwrap();
+
+        assert_eq!(
+            vec!["192.168.1.1".parse::<IpAddr>().unwrap(), "::1".parse().unwrap()],
+            hosts.addrs);
+        assert_eq!(
+            vec!["10.0.0.0/8".parse::<IpNetwork>().unwrap(), "2001:db8::/32".parse().unwrap()],
+            hosts.networks);
+    }
+
+    #[test]
+    fn snake_case_columns() {
+        use config::{ColumnCase, Config};
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct User {
+            user_name: String,
+        }
+
+        let connection = setup_and_connect_to_db();
+
+        let results = connection.query(r#"SELECT 'Jane'::text AS "userName""#, &[])
+            .unwrap();
+
+        let row = results.get(0);
+
+        let config = Config::new().column_case(ColumnCase::ToSnake);
+        let user: User = super::from_row_with_config(row, config).unwrap();
+
+        assert_eq!("Jane", user.user_name);
+    }
+
+    #[cfg(feature = "with-iso8601")]
+    #[test]
+    fn interval_iso8601() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Lease {
+            term: ::iso8601::Duration,
+        }
+
+        let connection = setup_and_connect_to_db();
+
+        let results = connection.query(
+            "SELECT '1 year 2 mons 3 days'::interval AS term", &[]).unwrap();
+
+        let row = results.get(0);
+
+        let lease: Lease = super::from_row(row).unwrap();
+
+        assert_eq!(::iso8601::Duration::YMDHMS {
+            year: 1,
+            month: 2,
+            day: 3,
+            hour: 0,
+            minute: 0,
+            second: 0,
+            millisecond: 0,
+        }, lease.term);
+    }
+
+    #[test]
+    fn cached_signature_mismatch() {
+        use config::{ColumnCase, Config};
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct User {
+            user_id: i32,
+        }
+
+        let connection = setup_and_connect_to_db();
+
+        let config = Config::new().column_case(ColumnCase::ToSnake);
+        let mut cache = super::CachedDeserializer::<User>::with_config(config);
+
+        let results = connection.query(r#"SELECT 1 AS "userId""#, &[]).unwrap();
+        let users = cache.from_rows(results).unwrap();
+        assert_eq!(vec![User { user_id: 1 }], users);
+        assert!(cache.is_cached());
+
+        let results = connection.query(
+            r#"SELECT 2 AS "accountId", 3 AS "userId""#, &[]).unwrap();
+        let users = cache.from_rows(results).unwrap();
+        assert_eq!(vec![User { user_id: 3 }], users);
+    }
+
+    #[test]
+    fn bool_columns_into_flags() {
+        #[derive(Debug, Deserialize)]
+        struct PermissionColumns {
+            can_read: bool,
+            can_write: bool,
+            can_delete: bool,
+        }
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        #[serde(from = "PermissionColumns")]
+        struct Permissions(u8);
+
+        impl Permissions {
+            const READ: u8 = 0b001;
+            const WRITE: u8 = 0b010;
+            const DELETE: u8 = 0b100;
+        }
+
+        impl From<PermissionColumns> for Permissions {
+            fn from(columns: PermissionColumns) -> Self {
+                let mut bits = 0;
+                if columns.can_read { bits |= Self::READ; }
+                if columns.can_write { bits |= Self::WRITE; }
+                if columns.can_delete { bits |= Self::DELETE; }
+                Permissions(bits)
+            }
+        }
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct User {
+            name: String,
+            #[serde(flatten)]
+            permissions: Permissions,
+        }
+
+        let connection = setup_and_connect_to_db();
+
+        let results = connection.query("SELECT 'Jane'::text AS name,
+            true AS can_read, false AS can_write, true AS can_delete", &[])
+            .unwrap();
+
+        let row = results.get(0);
+
+        let user: User = super::from_row(row).unwrap();
+
+        assert_eq!(Permissions(Permissions::READ | Permissions::DELETE), user.permissions);
+    }
+
+    #[test]
+    fn non_null_option() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Account {
+            #[serde(deserialize_with = "super::non_null")]
+            email: Option<String>,
+        }
+
+        let connection = setup_and_connect_to_db();
+
+        let results = connection.query("SELECT 'goku@example.com'::text AS email", &[])
+            .unwrap();
+
+        let row = results.get(0);
+
+        let account: Account = super::from_row(row).unwrap();
+
+        assert_eq!(Account { email: Some(String::from("goku@example.com")) }, account);
+
+        let results = connection.query("SELECT NULL::text AS email", &[])
+            .unwrap();
+
+        let row = results.get(0);
+
+        match super::from_row::<Account>(row) {
+            Err(super::Error::Column { ref name, .. }) => assert_eq!("email", name),
+            other => panic!("expected a column error, found {:?}", other),
+        }
+    }
+
+    #[test]
+    fn money_string() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Invoice {
+            total: String,
+        }
+
+        let connection = setup_and_connect_to_db();
+
+        let results = connection.query("SELECT '$1,234.56'::money AS total", &[])
+            .unwrap();
+
+        let row = results.get(0);
+
+        let invoice: Invoice = super::from_row(row).unwrap();
+
+        assert_eq!("1234.56", invoice.total);
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn nested_json_struct() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Meta {
+            tags: Vec<String>,
+        }
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct User {
+            name: String,
+        }
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Document {
+            user: User,
+            meta: Meta,
+        }
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Record {
+            id: i32,
+            data: Document,
+        }
+
+        let connection = setup_and_connect_to_db();
+
+        let results = connection.query(r#"SELECT 1 AS id,
+            '{"user":{"name":"x"},"meta":{"tags":["a","b"]}}'::jsonb AS data"#, &[])
+            .unwrap();
+
+        let row = results.get(0);
+
+        let record: Record = super::from_row(row).unwrap();
+
+        assert_eq!(Record {
+            id: 1,
+            data: Document {
+                user: User { name: String::from("x") },
+                meta: Meta { tags: vec![String::from("a"), String::from("b")] },
+            },
+        }, record);
+    }
+
+    #[test]
+    fn trim_transform() {
+        use raw::RawValue;
+        use tokio_postgres::types::Type;
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct User {
+            name: String,
+        }
+
+        let connection = setup_and_connect_to_db();
+
+        let results = connection.query("SELECT '  Jane '::text AS name", &[])
+            .unwrap();
+
+        let row = results.get(0);
+
+        let trim: super::Transform = Box::new(|_, value: RawValue| {
+            if *value.ty() != Type::TEXT {
+                return Ok(value);
+            }
+
+            let trimmed = value.bytes()
+                .map(|bytes| String::from_utf8_lossy(bytes).trim().as_bytes().to_vec());
+            Ok(value.with_bytes(trimmed))
+        });
+
+        let user: User = super::from_row_with_transforms(row, &[trim]).unwrap();
+
+        assert_eq!("Jane", user.name);
+    }
+
+    #[cfg(feature = "with-chrono")]
+    #[test]
+    fn timestamptz_array() {
+        use chrono::{DateTime, TimeZone, Utc};
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Schedule {
+            runs: Vec<DateTime<Utc>>,
+        }
+
+        let connection = setup_and_connect_to_db();
+
+        let results = connection.query("SELECT ARRAY['2020-01-01T00:00:00Z',
+            '2021-06-15T12:30:00+02']::timestamptz[] AS runs", &[])
+            .unwrap();
+
+        let row = results.get(0);
+
+        let schedule: Schedule = super::from_row(row).unwrap();
+
+        assert_eq!(vec![
+            Utc.ymd(2020, 1, 1).and_hms(0, 0, 0),
+            Utc.ymd(2021, 6, 15).and_hms(10, 30, 0),
+        ], schedule.runs);
+    }
+
+    #[test]
+    fn boxed_bytea() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Blob {
+            data: Box<[u8]>,
+        }
+
+        let connection = setup_and_connect_to_db();
+
+        let results = connection.query("SELECT '\\x0102030405'::bytea AS data", &[])
+            .unwrap();
+
+        let row = results.get(0);
+
+        let blob: Blob = super::from_row(row).unwrap();
+
+        assert_eq!(5, blob.data.len());
+        assert_eq!(&[1, 2, 3, 4, 5][..], &*blob.data);
+    }
+
+    #[test]
+    fn cancel_partway() {
+        use super::CancellationToken;
+
+        thread_local! {
+            static TOKEN: CancellationToken = CancellationToken::new();
+        }
+
+        fn cancel_at_two<'de, D: ::serde::Deserializer<'de>>(deserializer: D)
+            -> ::std::result::Result<i32, D::Error>
+        {
+            let id = <i32 as ::serde::Deserialize>::deserialize(deserializer)?;
+            if id == 2 {
+                TOKEN.with(|token| token.cancel());
+            }
+            Ok(id)
+        }
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Buu {
+            #[serde(deserialize_with = "cancel_at_two")]
+            id: i32,
+        }
+
+        let connection = setup_and_connect_to_db();
+
+        let results = connection.query(
+            "SELECT generate_series(1, 5) AS id", &[]).unwrap();
+
+        let token = TOKEN.with(|token| token.clone());
+        let result = super::from_rows_cancellable::<Buu>(results, &token);
+
+        assert_eq!(Err(super::Error::Cancelled), result);
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn json_tagged_enum() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        #[serde(tag = "kind", rename_all = "lowercase")]
+        enum Shape {
+            A { x: i32 },
+            B { y: String },
+        }
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Drawing {
+            shape: Shape,
+        }
+
+        let connection = setup_and_connect_to_db();
+
+        let results = connection.query(
+            r#"SELECT '{"kind":"a","x":1}'::jsonb AS shape"#, &[]).unwrap();
+
+        let row = results.get(0);
+
+        let drawing: Drawing = super::from_row(row).unwrap();
+
+        assert_eq!(Shape::A { x: 1 }, drawing.shape);
+    }
+
+    #[test]
+    fn raw_array_bytes() {
+        use std::fmt;
+
+        use serde::de::{self, Visitor};
+
+        use array::RawArray;
+
+        struct BytesVisitor;
+
+        impl<'de> Visitor<'de> for BytesVisitor {
+            type Value = Vec<u8>;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("raw bytes")
+            }
+
+            fn visit_bytes<E: de::Error>(self, v: &[u8]) -> Result<Vec<u8>, E> {
+                Ok(v.to_vec())
+            }
+        }
+
+        fn raw_bytes<'de, D: de::Deserializer<'de>>(deserializer: D)
+            -> Result<Vec<u8>, D::Error>
+        {
+            deserializer.deserialize_bytes(BytesVisitor)
+        }
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Scores {
+            #[serde(deserialize_with = "raw_bytes")]
+            points: Vec<u8>,
+        }
+
+        let connection = setup_and_connect_to_db();
+
+        let results = connection.query("SELECT ARRAY[1, 2, 3]::int4[] AS points", &[])
+            .unwrap();
+
+        let row = results.get(0);
+
+        let scores: Scores = super::from_row(row).unwrap();
+        let array = RawArray::from_sql(&scores.points).unwrap();
+        let points: Vec<i32> = array.elements().iter()
+            .map(|element| {
+                let bytes = element.unwrap();
+                i32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])
+            })
+            .collect();
+
+        assert_eq!(vec![1, 2, 3], points);
+    }
+
+    #[test]
+    fn partial_rows() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct User {
+            id: i32,
+            name: String,
+        }
+
+        let connection = setup_and_connect_to_db();
+
+        let results = connection.query("SELECT * FROM (VALUES
+            (1, 'Jane'::text), (2, NULL), (3, 'Alice')) AS t(id, name)", &[])
+            .unwrap();
+
+        let (users, errors) = super::from_rows_partial::<User>(results);
+
+        assert_eq!(vec![
+            User { id: 1, name: String::from("Jane") },
+            User { id: 3, name: String::from("Alice") },
+        ], users);
+        assert_eq!(1, errors.len());
+        assert_eq!(1, errors[0].0);
+    }
+
+    #[test]
+    fn uuid_u128() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Session {
+            id: u128,
+        }
+
+        let connection = setup_and_connect_to_db();
+
+        let results = connection.query(
+            "SELECT '67e55044-10b1-426f-9247-bb680e5fe0c8'::uuid AS id", &[])
+            .unwrap();
+
+        let row = results.get(0);
+
+        let session: Session = super::from_row(row).unwrap();
+
+        assert_eq!(0x67e5_5044_10b1_426f_9247_bb68_0e5f_e0c8, session.id);
+    }
+
+    #[test]
+    fn schema_type_mismatch() {
+        use schema::{validate_schema, SchemaMismatch};
+        use tokio_postgres::types::Type;
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct User {
+            id: i32,
+            name: String,
+            age: i32,
+        }
+
+        let connection = setup_and_connect_to_db();
+
+        let results = connection.query(
+            "SELECT 1 AS id, 'Jane'::text AS name, '23'::text AS age", &[])
+            .unwrap();
+
+        let row = results.get(0);
+
+        assert_eq!(Err(vec![SchemaMismatch::TypeMismatch {
+            column: String::from("age"),
+            expected: "i32",
+            found: Type::TEXT,
+        }]), validate_schema::<User>(row.columns()));
+    }
+
+    #[test]
+    fn schema_missing_column() {
+        use schema::{validate_schema, SchemaMismatch};
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct User {
+            id: i32,
+            nickname: Option<String>,
+            name: String,
+            #[serde(default)]
+            score: i32,
+            email: String,
+        }
+
+        let connection = setup_and_connect_to_db();
+
+        let results = connection.query("SELECT 1 AS id", &[]).unwrap();
+
+        let row = results.get(0);
+
+        assert_eq!(Err(vec![
+            SchemaMismatch::MissingColumn(String::from("name")),
+            SchemaMismatch::MissingColumn(String::from("email")),
+        ]), validate_schema::<User>(row.columns()));
+
+        let results = connection.query(
+            "SELECT 1 AS id, 'Jane'::text AS name, 'jane@example.com'::text AS email", &[])
+            .unwrap();
+
+        let row = results.get(0);
+
+        assert_eq!(Ok(()), validate_schema::<User>(row.columns()));
+    }
+
+    #[cfg(feature = "with-chrono")]
+    #[test]
+    fn interval_chrono_duration() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Offset {
+            #[serde(deserialize_with = "super::chrono_duration")]
+            shift: ::chrono::Duration,
+        }
+
+        let connection = setup_and_connect_to_db();
+
+        let results = connection.query(
+            "SELECT '-1 day -2 hours'::interval AS shift", &[]).unwrap();
+
+        let row = results.get(0);
+
+        let offset: Offset = super::from_row(row).unwrap();
+
+        assert_eq!(::chrono::Duration::hours(-26), offset.shift);
+
+        let results = connection.query(
+            "SELECT '1 mon 1 day'::interval AS shift", &[]).unwrap();
+
+        let row = results.get(0);
+
+        assert!(super::from_row::<Offset>(row).is_err());
+    }
+
+    #[test]
+    fn bytea_buffer_pool() {
+        use std::fmt;
+
+        use serde::de::{self, Visitor};
+
+        struct BufVisitor;
+
+        impl<'de> Visitor<'de> for BufVisitor {
+            type Value = Vec<u8>;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a byte buffer")
+            }
+
+            fn visit_byte_buf<E: de::Error>(self, v: Vec<u8>) -> Result<Vec<u8>, E> {
+                Ok(v)
+            }
+        }
+
+        fn byte_buf<'de, D: de::Deserializer<'de>>(deserializer: D)
+            -> Result<Vec<u8>, D::Error>
+        {
+            deserializer.deserialize_byte_buf(BufVisitor)
+        }
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Blob {
+            #[serde(deserialize_with = "byte_buf")]
+            data: Vec<u8>,
+        }
+
+        let connection = setup_and_connect_to_db();
+
+        // Recycled buffers still holding stale bytes.
+        let mut pool = super::BufferPool::new();
+        pool.put(vec![0xff; 16]);
+        pool.put(vec![0xee; 2]);
+
+        let results = connection.query("SELECT * FROM (VALUES
+            ('\\x0102'::bytea), ('\\x030405'), ('\\x06')) AS t(data)", &[])
+            .unwrap();
+
+        let blobs: Vec<Blob> = super::from_rows_with_buffer_pool(results, &mut pool).unwrap();
+
+        assert_eq!(vec![
+            Blob { data: vec![1, 2] },
+            Blob { data: vec![3, 4, 5] },
+            Blob { data: vec![6] },
+        ], blobs);
+        assert!(pool.is_empty());
+
+        // Buffers put back are read into again by the next call.
+        let buffers: Vec<_> = blobs.iter().map(|blob| blob.data.as_ptr()).collect();
+        for blob in blobs {
+            pool.put(blob.data);
+        }
+
+        let results = connection.query("SELECT * FROM (VALUES
+            ('\\x07'::bytea), ('\\x08'), ('\\x09')) AS t(data)", &[])
+            .unwrap();
+
+        let blobs: Vec<Blob> = super::from_rows_with_buffer_pool(results, &mut pool).unwrap();
+
+        assert_eq!(vec![
+            Blob { data: vec![7] },
+            Blob { data: vec![8] },
+            Blob { data: vec![9] },
+        ], blobs);
+        assert!(blobs.iter().all(|blob| buffers.contains(&blob.data.as_ptr())));
+        assert!(pool.is_empty());
+    }
+
+    #[test]
+    fn pg_lsn() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        #[serde(transparent)]
+        struct PgLsn(u64);
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Slot {
+            flushed: Vec<PgLsn>,
+        }
+
+        let connection = setup_and_connect_to_db();
+
+        let results = connection.query(
+            "SELECT ARRAY['16/B374D848', '0/0']::pg_lsn[] AS flushed", &[])
+            .unwrap();
+
+        let row = results.get(0);
+
+        let slot: Slot = super::from_row(row).unwrap();
+
+        assert_eq!(vec![PgLsn(0x16_B374_D848), PgLsn(0)], slot.flushed);
+
+        let results = connection.query(
+            "SELECT '0/1'::pg_lsn AS start, '16/B374D848'::pg_lsn AS finish", &[])
+            .unwrap();
+
+        let row = results.get(0);
+
+        let range: (PgLsn, PgLsn) = super::from_row(row).unwrap();
+
+        assert_eq!((PgLsn(1), PgLsn(0x16_B374_D848)), range);
+    }
+
+    #[test]
+    fn u64_array() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Counters {
+            hits: Vec<u64>,
+            misses: Vec<u64>,
+        }
+
+        let connection = setup_and_connect_to_db();
+
+        let results = connection.query(
+            "SELECT ARRAY[1, 9000000000]::int8[] AS hits, ARRAY[2, 3]::int4[] AS misses", &[])
+            .unwrap();
+
+        let row = results.get(0);
+
+        let counters: Counters = super::from_row(row).unwrap();
+
+        assert_eq!(Counters { hits: vec![1, 9_000_000_000], misses: vec![2, 3] }, counters);
+    }
+
+    #[test]
+    fn geometry_wkt() {
+        use config::Config;
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Shapes {
+            point: String,
+            segment: String,
+            area: String,
+            path: String,
+            polygon: String,
+        }
+
+        let connection = setup_and_connect_to_db();
+
+        let results = connection.query("SELECT
+            '(1,2)'::point AS point,
+            '[(0,0),(1.5,2)]'::lseg AS segment,
+            '((1,1),(0,0))'::box AS area,
+            '[(0,0),(1,1),(2,0)]'::path AS path,
+            '((0,0),(0,1),(1,1))'::polygon AS polygon", &[])
+            .unwrap();
+
+        let row = results.get(0);
+
+        let config = Config::new().geometry_as_wkt(true);
+        let shapes: Shapes = super::from_row_with_config(row, config).unwrap();
+
+        assert_eq!(Shapes {
+            point: String::from("POINT(1 2)"),
+            segment: String::from("LINESTRING(0 0, 1.5 2)"),
+            area: String::from("POLYGON((1 1, 0 1, 0 0, 1 0, 1 1))"),
+            path: String::from("LINESTRING(0 0, 1 1, 2 0)"),
+            polygon: String::from("POLYGON((0 0, 0 1, 1 1, 0 0))"),
+        }, shapes);
+    }
+
+    #[test]
+    fn null_aggregate() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Department {
+            dept: String,
+            avg_salary: Option<f64>,
+        }
+
+        let connection = setup_and_connect_to_db();
+
+        let results = connection.query("SELECT dept, AVG(salary)::float8 AS avg_salary
+            FROM (VALUES ('empty'::text, NULL::int4), ('sales', 10), ('sales', 20))
+                AS emp(dept, salary)
+            GROUP BY dept ORDER BY dept", &[])
+            .unwrap();
+
+        let departments: Vec<Department> = super::from_rows(results).unwrap();
+
+        assert_eq!(vec![
+            Department { dept: String::from("empty"), avg_salary: None },
+            Department { dept: String::from("sales"), avg_salary: Some(15.0) },
+        ], departments);
+    }
+
+    #[test]
+    fn money_f64() {
+        use config::Config;
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Invoice {
+            total: f64,
+        }
+
+        let connection = setup_and_connect_to_db();
+
+        let results = connection.query("SELECT '$12.34'::money AS total", &[])
+            .unwrap();
+
+        let row = results.get(0);
+
+        let config = Config::new().coerce_money_to_f64(true);
+        let invoice: Invoice = super::from_row_with_config(row, config).unwrap();
+
+        assert!((invoice.total - 12.34).abs() < 1e-9);
+    }
+
+    #[test]
+    fn array_agg_children() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Parent {
+            id: i32,
+            child_names: Vec<String>,
+        }
+
+        let connection = setup_and_connect_to_db();
+
+        let results = connection.query("SELECT parent.id,
+                array_agg(child.name ORDER BY child.name) AS child_names
+            FROM (VALUES (1)) AS parent(id)
+            JOIN (VALUES (1, 'Bob'::text), (1, 'Alice')) AS child(parent_id, name)
+                ON child.parent_id = parent.id
+            GROUP BY parent.id", &[])
+            .unwrap();
+
+        let row = results.get(0);
+
+        let parent: Parent = super::from_row(row).unwrap();
+
+        assert_eq!(Parent {
+            id: 1,
+            child_names: vec![String::from("Alice"), String::from("Bob")],
+        }, parent);
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn json_numbers() {
+        #[derive(Debug, Deserialize)]
+        struct Reading {
+            count: u64,
+            big: f64,
+            ratio: f64,
+            missing: f64,
+        }
+
+        let connection = setup_and_connect_to_db();
+
+        let results = connection.query("SELECT
+            '18446744073709551615'::jsonb AS count,
+            '123456789012345678901234567890'::jsonb AS big,
+            '0.125'::jsonb AS ratio,
+            'null'::jsonb AS missing", &[])
+            .unwrap();
+
+        let row = results.get(0);
+
+        let reading: Reading = super::from_row(row).unwrap();
+
+        assert_eq!(::std::u64::MAX, reading.count);
+        assert_eq!(1.2345678901234568e29, reading.big);
+        assert_eq!(0.125, reading.ratio);
+        assert!(reading.missing.is_nan());
+    }
+
+    #[test]
+    fn case_insensitive_enum() {
+        use config::Config;
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        #[serde(rename_all = "lowercase")]
+        enum Status {
+            Active,
+            Suspended,
+        }
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Account {
+            status: Status,
+        }
+
+        let connection = setup_and_connect_to_db();
+
+        let results = connection.query("SELECT 'ACTIVE'::text AS status", &[])
+            .unwrap();
+
+        let row = results.get(0);
+
+        assert!(super::from_row::<Account>(row).is_err());
+
+        let row = results.get(0);
+
+        let config = Config::new().case_insensitive_enums(true);
+        let account: Account = super::from_row_with_config(row, config).unwrap();
+
+        assert_eq!(Status::Active, account.status);
+    }
+
+    #[test]
+    fn typed_value() {
+        use typed::Typed;
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Buu {
+            id: Typed<i64>,
+        }
+
+        let connection = setup_and_connect_to_db();
+
+        let results = connection.query("SELECT 7::int8 AS id", &[]).unwrap();
+
+        let row = results.get(0);
+
+        let buu: Buu = super::from_row(row).unwrap();
+
+        assert_eq!(Typed { value: 7, pg_type: String::from("int8") }, buu.id);
+    }
+
+    #[test]
+    fn bytea_byte_buf() {
+        use serde_bytes::ByteBuf;
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Blob {
+            data: ByteBuf,
+        }
+
+        let connection = setup_and_connect_to_db();
+
+        let results = connection.query("SELECT '\\xdeadbeef'::bytea AS data", &[])
+            .unwrap();
+
+        let row = results.get(0);
+
+        let blob: Blob = super::from_row(row).unwrap();
+
+        assert_eq!(vec![0xde, 0xad, 0xbe, 0xef], blob.data.into_vec());
+    }
+
+    #[cfg(feature = "with-ipnet")]
+    #[test]
+    fn ipnet_networks() {
+        use ipnet::IpNet;
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Route {
+            network: IpNet,
+            #[serde(deserialize_with = "super::ipnet")]
+            host: IpNet,
+        }
+
+        let connection = setup_and_connect_to_db();
+
+        let results = connection.query("SELECT
+            '10.0.0.0/8'::cidr AS network, '192.168.1.1'::inet AS host", &[]).unwrap();
+
+        let row = results.get(0);
+
+        let route: Route = super::from_row(row).unwrap();
+
+        assert_eq!("10.0.0.0/8".parse::<IpNet>().unwrap(), route.network);
+        assert_eq!("192.168.1.1/32".parse::<IpNet>().unwrap(), route.host);
+
+        let results = connection.query("SELECT
+            '2001:db8::/32'::cidr AS network, '::1'::inet AS host", &[]).unwrap();
+
+        let row = results.get(0);
+
+        let route: Route = super::from_row(row).unwrap();
+
+        assert_eq!("2001:db8::/32".parse::<IpNet>().unwrap(), route.network);
+        assert_eq!("::1/128".parse::<IpNet>().unwrap(), route.host);
+    }
+
+    #[test]
+    fn override_columns() {
+        use std::collections::HashMap;
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct User {
+            id: i32,
+            #[serde(rename = "user_name")]
+            name: String,
+            #[serde(rename = "user_age")]
+            age: i32,
+        }
+
+        let connection = setup_and_connect_to_db();
+
+        let results = connection.query(
+            "SELECT 1 AS id, 'Jane'::text AS user_name, 23 AS years", &[]).unwrap();
+
+        let row = results.get(0);
+
+        let mut overrides = HashMap::new();
+        overrides.insert("user_age", "years");
+
+        let user: User = super::from_row_with_overrides(row, &overrides).unwrap();
+
+        assert_eq!(User { id: 1, name: String::from("Jane"), age: 23 }, user);
+
+        // The override wins over a column named after the field.
+        let results = connection.query(
+            "SELECT 1 AS id, 'Jane'::text AS user_name, 99 AS user_age, 23 AS years", &[]).unwrap();
+
+        let row = results.get(0);
+
+        let user: User = super::from_row_with_overrides(row, &overrides).unwrap();
+
+        assert_eq!(User { id: 1, name: String::from("Jane"), age: 23 }, user);
+    }
+
+    #[test]
+    fn enum_array() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        #[serde(rename_all = "lowercase")]
+        enum Mood {
+            Happy,
+            #[serde(rename = "down")]
+            Sad,
+        }
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Diary {
+            moods: Vec<Mood>,
+        }
+
+        let connection = setup_and_connect_to_db();
+
+        connection.execute("CREATE TYPE mood AS ENUM ('happy', 'down', 'meh')", &[])
+            .unwrap();
+
+        let results = connection.query(
+            "SELECT ARRAY['happy', 'down']::mood[] AS moods", &[]).unwrap();
+
+        let row = results.get(0);
+
+        let diary: Diary = super::from_row(row).unwrap();
+
+        assert_eq!(vec![Mood::Happy, Mood::Sad], diary.moods);
+
+        let results = connection.query(
+            "SELECT ARRAY['happy', 'meh']::mood[] AS moods", &[]).unwrap();
+
+        let row = results.get(0);
+
+        assert!(super::from_row::<Diary>(row).is_err());
+
+        connection.execute("DROP TYPE mood", &[]).unwrap();
+    }
+
+    #[test]
+    fn distinct_on() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Latest {
+            score: i32,
+            name: String,
+            team: String,
+        }
+
+        let connection = setup_and_connect_to_db();
+
+        let results = connection.query("SELECT DISTINCT ON (team) team, name, score
+            FROM (VALUES ('red'::text, 'Jane'::text, 3), ('red', 'Bob', 5),
+                ('blue', 'Alice', 4)) AS t(team, name, score)
+            ORDER BY team, score DESC", &[])
+            .unwrap();
+
+        let latest: Vec<Latest> = super::from_rows(results).unwrap();
+
+        assert_eq!(vec![
+            Latest { score: 4, name: String::from("Alice"), team: String::from("blue") },
+            Latest { score: 5, name: String::from("Bob"), team: String::from("red") },
+        ], latest);
+    }
+
+    #[test]
+    fn option_conversion_error() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Buu {
+            width: Option<i32>,
+        }
+
+        let connection = setup_and_connect_to_db();
+
+        let results = connection.query("SELECT 'wide'::text AS width", &[])
+            .unwrap();
+
+        let row = results.get(0);
+
+        match super::from_row::<Buu>(row) {
+            Err(super::Error::Column { ref name, .. }) => assert_eq!("width", name),
+            other => panic!("expected a column error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn enums() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Goku {
+            hair: HairColour,
+        }
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        #[serde(rename_all = "lowercase")]
+        enum HairColour {
+            Black,
+            Yellow,
+            Blue,
+        }
+
+        let connection = setup_and_connect_to_db();
+
+        connection.execute("CREATE TYPE hair_colour as ENUM (
+            'black',
+            'yellow',
+            'blue'
+        )", &[]).unwrap();
+
+        connection.execute("CREATE TABLE Gokus (hair hair_colour)",
+        &[]).unwrap();
+
+        connection.execute("INSERT INTO Gokus VALUES ('black')", &[])
+            .unwrap();
+
+        let results = connection.query("SELECT * FROM Gokus", &[])
+            .unwrap();
+
+        let row = results.get(0);
+
+        let goku: Goku = super::from_row(row).unwrap();
+
+        assert_eq!(HairColour::Black, goku.hair);
+
+        connection.execute("DROP TABLE Gokus", &[]).unwrap();
+        connection.execute("DROP TYPE hair_colour", &[]).unwrap();
+    }
+
+    #[test]
+    fn smallint_u8() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Pixel {
+            red: u8,
+        }
+
+        let connection = setup_and_connect_to_db();
+
+        let results = connection.query("SELECT 200::int2 AS red", &[]).unwrap();
+
+        let row = results.get(0);
+
+        let pixel: Pixel = super::from_row(row).unwrap();
+
+        assert_eq!(200, pixel.red);
+
+        let results = connection.query("SELECT 300::int2 AS red", &[]).unwrap();
+
+        let row = results.get(0);
+
+        assert_eq!(
+            Err(super::Error::InvalidType(String::from("red value out of range"))),
+            super::from_row::<Pixel>(row));
+    }
+
+    #[test]
+    fn int_u16() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Service {
+            port: u16,
+        }
+
+        let connection = setup_and_connect_to_db();
+
+        let results = connection.query("SELECT 40000 AS port", &[]).unwrap();
+
+        let row = results.get(0);
+
+        let service: Service = super::from_row(row).unwrap();
+
+        assert_eq!(40000, service.port);
+
+        let results = connection.query("SELECT -1 AS port", &[]).unwrap();
+
+        let row = results.get(0);
+
+        assert_eq!(
+            Err(super::Error::InvalidType(String::from("port value out of range"))),
+            super::from_row::<Service>(row));
+    }
+
+    #[test]
+    fn bigint_u64() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Counter {
+            hits: u64,
+        }
+
+        let connection = setup_and_connect_to_db();
+
+        let results = connection.query("SELECT 9000000000::int8 AS hits", &[]).unwrap();
+
+        let row = results.get(0);
+
+        let counter: Counter = super::from_row(row).unwrap();
+
+        assert_eq!(9_000_000_000, counter.hits);
+
+        let results = connection.query("SELECT -1::int8 AS hits", &[]).unwrap();
+
+        let row = results.get(0);
+
+        assert_eq!(
+            Err(super::Error::InvalidType(String::from(
+                "hits value is negative, so it can't be a u64"))),
+            super::from_row::<Counter>(row));
+    }
+
+    #[test]
+    fn single_char() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Flag {
+            active: char,
+        }
+
+        let connection = setup_and_connect_to_db();
+
+        let results = connection.query("SELECT 'Y'::char(1) AS active", &[]).unwrap();
+
+        let row = results.get(0);
+
+        let flag: Flag = super::from_row(row).unwrap();
+
+        assert_eq!('Y', flag.active);
+
+        let results = connection.query("SELECT 'YES'::varchar AS active", &[]).unwrap();
+
+        let row = results.get(0);
+
+        assert_eq!(
+            Err(super::Error::InvalidType(String::from(
+                "active expected a single character, found \"YES\""))),
+            super::from_row::<Flag>(row));
+    }
+
+    #[test]
+    fn same_row_twice() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Name {
+            name: String,
+        }
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Age {
+            age: i32,
+        }
+
+        let connection = setup_and_connect_to_db();
+
+        let results = connection.query("SELECT 'Alice'::text AS name, 30::int4 AS age", &[]).unwrap();
+
+        let row = results.get(0);
+
+        let name: Name = super::from_row_ref(&row).unwrap();
+        let age: Age = super::from_row_ref(&row).unwrap();
+
+        assert_eq!(Name { name: String::from("Alice") }, name);
+        assert_eq!(Age { age: 30 }, age);
+    }
+
+    #[test]
+    fn borrowed_str() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct User<'a> {
+            #[serde(borrow)]
+            name: &'a str,
+            #[serde(borrow)]
+            nickname: Option<&'a str>,
+        }
+
+        let connection = setup_and_connect_to_db();
+
+        let results = connection.query("SELECT 'Alice'::text AS name, NULL::varchar AS nickname", &[]).unwrap();
+
+        let row = results.get(0);
+
+        let user: User = super::from_row_ref(&row).unwrap();
+
+        assert_eq!(User { name: "Alice", nickname: None }, user);
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn jsonb_value() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Data {
+            a: i32,
+        }
+
+        #[derive(Debug, Deserialize)]
+        struct Untyped {
+            data: ::serde_json::Value,
+        }
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Typed {
+            data: Data,
+        }
+
+        let connection = setup_and_connect_to_db();
+
+        connection.execute("CREATE TABLE IF NOT EXISTS JsonBuu (
+                    data JSONB NOT NULL
+        )", &[]).unwrap();
+
+        connection.execute(r#"INSERT INTO JsonBuu (data) VALUES ('{"a":1}')"#, &[]).unwrap();
+
+        let results = connection.query("SELECT data FROM JsonBuu", &[]).unwrap();
+
+        let row = results.get(0);
+
+        let untyped: Untyped = super::from_row_ref(&row).unwrap();
+        let typed: Typed = super::from_row_ref(&row).unwrap();
+
+        assert_eq!(
+            ::serde_json::from_str::<::serde_json::Value>(r#"{"a":1}"#).unwrap(),
+            untyped.data);
+        assert_eq!(Data { a: 1 }, typed.data);
+
+        connection.execute("DROP TABLE JsonBuu", &[]).unwrap();
+    }
+
+    #[cfg(feature = "with-chrono")]
+    #[test]
+    fn chrono_timestamps() {
+        use chrono::{DateTime, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc};
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Event {
+            created_at: DateTime<Utc>,
+            local_at: NaiveDateTime,
+            day: NaiveDate,
+            starts: NaiveTime,
+        }
+
+        let connection = setup_and_connect_to_db();
+
+        connection.execute("CREATE TABLE IF NOT EXISTS EventBuu (
+                    created_at TIMESTAMPTZ NOT NULL,
+                    local_at TIMESTAMP NOT NULL,
+                    day DATE NOT NULL,
+                    starts TIME NOT NULL
+        )", &[]).unwrap();
+
+        connection.execute("INSERT INTO EventBuu (created_at, local_at, day, starts)
+            VALUES ('2021-06-15T12:30:00+02', '2021-06-15 12:30:00.25', '2021-06-15', '08:15:00')",
+            &[]).unwrap();
+
+        let results = connection.query("SELECT created_at, local_at, day, starts FROM EventBuu", &[])
+            .unwrap();
+
+        let row = results.get(0);
+
+        let event: Event = super::from_row(row).unwrap();
+
+        assert_eq!(Utc.ymd(2021, 6, 15).and_hms(10, 30, 0), event.created_at);
+        assert_eq!(NaiveDate::from_ymd(2021, 6, 15).and_hms_milli(12, 30, 0, 250), event.local_at);
+        assert_eq!(NaiveDate::from_ymd(2021, 6, 15), event.day);
+        assert_eq!(NaiveTime::from_hms(8, 15, 0), event.starts);
+
+        connection.execute("DROP TABLE EventBuu", &[]).unwrap();
+    }
+
+    #[cfg(feature = "with-uuid")]
+    #[test]
+    fn uuid() {
+        use uuid::Uuid;
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Session {
+            id: Uuid,
+        }
+
+        let connection = setup_and_connect_to_db();
+
+        connection.execute("CREATE TABLE IF NOT EXISTS SessionBuu (
+                    id UUID NOT NULL
+        )", &[]).unwrap();
+
+        connection.execute("INSERT INTO SessionBuu (id)
+            VALUES ('a0eebc99-9c0b-4ef8-bb6d-6bb9bd380a11')", &[]).unwrap();
+
+        let results = connection.query("SELECT id FROM SessionBuu", &[]).unwrap();
+
+        let row = results.get(0);
+
+        let session: Session = super::from_row(row).unwrap();
+
+        assert_eq!(Uuid::parse_str("a0eebc99-9c0b-4ef8-bb6d-6bb9bd380a11").unwrap(), session.id);
+
+        connection.execute("DROP TABLE SessionBuu", &[]).unwrap();
+    }
+
+    #[test]
+    fn any_by_column_type() {
+        use serde::de;
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        #[serde(untagged)]
+        enum Scalar {
+            Int(i64),
+            Text(String),
+        }
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Pair {
+            first: Scalar,
+            second: Scalar,
+        }
+
+        fn any<'de, D: de::Deserializer<'de>>(deserializer: D) -> Result<(), D::Error> {
+            deserializer.deserialize_any(de::IgnoredAny).map(|_| ())
+        }
+
+        #[derive(Debug, Deserialize)]
+        struct Search {
+            #[serde(deserialize_with = "any")]
+            #[allow(dead_code)]
+            document: (),
+        }
+
+        let connection = setup_and_connect_to_db();
+
+        let results = connection.query("SELECT 1::int4 AS first, 'x'::text AS second", &[]).unwrap();
+
+        let row = results.get(0);
+
+        let pair: Pair = super::from_row(row).unwrap();
+
+        assert_eq!(Pair { first: Scalar::Int(1), second: Scalar::Text(String::from("x")) }, pair);
+
+        let results = connection.query("SELECT 'a b'::tsvector AS document", &[]).unwrap();
+
+        let row = results.get(0);
+
+        assert_eq!(
+            Err(super::Error::UnsupportedColumnType(String::from("tsvector"))),
+            super::from_row::<Search>(row).map(|_| ()));
+    }
+
+    #[test]
+    fn native_arrays() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Arrays {
+            ints: Vec<i32>,
+            names: Vec<String>,
+            weights: Vec<f64>,
+            blob: Vec<u8>,
+        }
+
+        let connection = setup_and_connect_to_db();
+
+        let results = connection.query("SELECT ARRAY[1,2,3] AS ints,
+            ARRAY['a', 'b']::text[] AS names,
+            ARRAY[0.5, 1.5]::float8[] AS weights,
+            '\\x0102'::bytea AS blob", &[])
+            .unwrap();
+
+        let row = results.get(0);
+
+        let arrays: Arrays = super::from_row(row).unwrap();
+
+        assert_eq!(Arrays {
+            ints: vec![1, 2, 3],
+            names: vec![String::from("a"), String::from("b")],
+            weights: vec![0.5, 1.5],
+            blob: vec![1, 2],
+        }, arrays);
+    }
+
+    #[cfg(feature = "with-rust-decimal")]
+    #[test]
+    fn numeric_decimal() {
+        use rust_decimal::Decimal;
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Price {
+            amount: Decimal,
+        }
+
+        let connection = setup_and_connect_to_db();
+
+        let results = connection.query("SELECT 12345.6789::NUMERIC AS amount", &[]).unwrap();
+
+        let row = results.get(0);
+
+        let price: Price = super::from_row(row).unwrap();
+
+        assert_eq!(Decimal::new(123456789, 4), price.amount);
+    }
+
+    #[test]
+    fn numeric_text() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Measure {
+            ratio: String,
+            total: String,
+            debt: String,
+        }
+
+        let connection = setup_and_connect_to_db();
+
+        let results = connection.query("SELECT 0.100::numeric AS ratio,
+            100000000.00::numeric AS total,
+            -12.5::numeric AS debt", &[])
+            .unwrap();
+
+        let row = results.get(0);
+
+        let measure: Measure = super::from_row(row).unwrap();
+
+        assert_eq!(Measure {
+            ratio: String::from("0.100"),
+            total: String::from("100000000.00"),
+            debt: String::from("-12.5"),
+        }, measure);
+    }
+
+    #[test]
+    fn tuple_by_position() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Pair(i32, String);
+
+        let connection = setup_and_connect_to_db();
+
+        let results = connection.query("SELECT 1::int4 AS id, 'Goku'::text AS name", &[]).unwrap();
+
+        let row = results.get(0);
+
+        let (id, name): (i32, String) = super::from_row_ref(&row).unwrap();
+        let pair: Pair = super::from_row_ref(&row).unwrap();
+
+        assert_eq!((1, String::from("Goku")), (id, name));
+        assert_eq!(Pair(1, String::from("Goku")), pair);
+
+        assert_eq!(
+            Err(super::Error::Message(String::from("invalid length 2, expected a tuple of size 3"))),
+            super::from_row_ref::<(i32, String, bool)>(&row));
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn row_as_json_map() {
+        use std::collections::HashMap;
+
+        use serde_json::Value;
+
+        let connection = setup_and_connect_to_db();
+
+        let results = connection.query("SELECT 1 AS a, 'x' AS b, true AS c, NULL::int4 AS d", &[])
+            .unwrap();
+
+        let row = results.get(0);
+
+        let map: HashMap<String, Value> = super::from_row(row).unwrap();
+
+        assert_eq!(4, map.len());
+        assert_eq!(Value::from(1), map["a"]);
+        assert_eq!(Value::from("x"), map["b"]);
+        assert_eq!(Value::Bool(true), map["c"]);
+        assert_eq!(Value::Null, map["d"]);
+    }
+
+    #[test]
+    fn rows_iter() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Buu {
+            id: i32,
+        }
+
+        let connection = setup_and_connect_to_db();
+
+        let results = connection.query(
+            "SELECT generate_series(1, 3) AS id", &[]).unwrap();
+
+        let ids: Vec<i32> = super::from_rows_iter::<Buu, _>(results)
+            .map(|buu| buu.unwrap().id)
+            .filter(|id| id % 2 == 1)
+            .collect();
+
+        assert_eq!(vec![1, 3], ids);
+    }
+
+    #[test]
+    fn strict_columns() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Buu {
+            id: i32,
+        }
+
+        let connection = setup_and_connect_to_db();
+
+        let results = connection.query("SELECT 1 AS id, 'Goku'::text AS name", &[]).unwrap();
+
+        let row = results.get(0);
+
+        assert_eq!(
+            Err(super::Error::Message(String::from("unexpected column `name`"))),
+            super::from_row_strict::<Buu>(row));
+
+        let results = connection.query("SELECT 1 AS id", &[]).unwrap();
+
+        let row = results.get(0);
+
+        assert_eq!(Buu { id: 1 }, super::from_row_strict(row).unwrap());
+    }
+
+    #[test]
+    fn column_not_found() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Buu {
+            catchphrase: String,
+            width: i16,
+        }
+
+        let connection = setup_and_connect_to_db();
+
+        let results = connection.query("SELECT 'Woo Woo'::text AS catchphrase", &[]).unwrap();
+
+        let row = results.get(0);
+
+        assert_eq!(
+            Err(super::Error::ColumnNotFound { name: String::from("width"), index: 1 }),
+            super::from_row_ref::<Buu>(&row));
+
+        assert_eq!(
+            Err(super::Error::ColumnNotFound { name: String::from("width"), index: 1 }),
+            super::from_row_ordered::<Buu>(row));
+    }
+
+    #[test]
+    fn widening() {
+        use schema::validate_schema;
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Reading {
+            speed: f64,
+            small: i64,
+            medium: i64,
+        }
+
+        let connection = setup_and_connect_to_db();
+
+        let results = connection.query(
+            "SELECT 1.5::real AS speed, 7::int2 AS small, 70000::int4 AS medium", &[]).unwrap();
+
+        let row = results.get(0);
+
+        assert_eq!(Ok(()), validate_schema::<Reading>(row.columns()));
+
+        let reading: Reading = super::from_row(row).unwrap();
+
+        assert_eq!(Reading { speed: 1.5, small: 7, medium: 70000 }, reading);
+
+        let results = connection.query("SELECT 1.5::float8 AS speed", &[]).unwrap();
+
+        let row = results.get(0);
+
+        #[derive(Debug, Deserialize)]
+        struct Narrow {
+            #[allow(dead_code)]
+            speed: f32,
+        }
+
+        assert!(super::from_row::<Narrow>(row).is_err());
+    }
+
+    #[test]
+    fn from_postgres_error() {
+        use std::error::Error;
+
+        use tokio_postgres::row::Row;
+
+        fn count(row: &Row) -> super::Result<i64> {
+            Ok(row.try_get("missing")?)
+        }
+
+        let connection = setup_and_connect_to_db();
+
+        let results = connection.query("SELECT 1::int8 AS count", &[]).unwrap();
+
+        let row = results.get(0);
+
+        match count(&row) {
+            Err(ref err @ super::Error::Postgres(_)) => assert!(err.source().is_some()),
+            other => panic!("expected a postgres error, found {:?}", other),
+        }
+    }
+
+    #[test]
+    fn array_with_nulls() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Scores {
+            points: Vec<Option<i32>>,
+        }
+
+        let connection = setup_and_connect_to_db();
+
+        let results = connection.query("SELECT ARRAY[1, NULL, 3]::int4[] AS points", &[])
+            .unwrap();
+
+        let row = results.get(0);
+
+        let scores: Scores = super::from_row_ref(&row).unwrap();
+
+        assert_eq!(vec![Some(1), None, Some(3)], scores.points);
+
+        #[derive(Debug, Deserialize)]
+        struct Strict {
+            #[allow(dead_code)]
+            points: Vec<i32>,
+        }
+
+        assert!(super::from_row_ref::<Strict>(&row).is_err());
+    }
+
+    #[test]
+    fn fixed_size_bytea() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Key {
+            hash: [u8; 4],
+        }
+
+        let connection = setup_and_connect_to_db();
+
+        let results = connection.query("SELECT '\\x01020304'::bytea AS hash", &[]).unwrap();
+
+        let row = results.get(0);
+
+        let key: Key = super::from_row(row).unwrap();
+
+        assert_eq!(Key { hash: [1, 2, 3, 4] }, key);
+
+        let results = connection.query("SELECT '\\x010203'::bytea AS hash", &[]).unwrap();
+
+        let row = results.get(0);
+
+        assert_eq!(
+            Err(super::Error::InvalidType(String::from("hash expected 4 bytes, found 3"))),
+            super::from_row::<Key>(row));
+
+        let results = connection.query("SELECT '\\x0102030405'::bytea AS hash", &[]).unwrap();
+
+        let row = results.get(0);
+
+        assert_eq!(
+            Err(super::Error::InvalidType(String::from("hash expected 4 bytes, found 5"))),
+            super::from_row::<Key>(row));
+    }
+
+    #[test]
+    fn inet_ip_addr() {
+        use std::net::IpAddr;
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Peers {
+            v4: IpAddr,
+            v6: IpAddr,
+        }
+
+        let connection = setup_and_connect_to_db();
+
+        let results = connection.query(
+            "SELECT '192.168.1.1'::inet AS v4, '::1'::inet AS v6", &[]).unwrap();
+
+        let row = results.get(0);
+
+        let peers: Peers = super::from_row(row).unwrap();
+
+        assert_eq!(Peers {
+            v4: "192.168.1.1".parse().unwrap(),
+            v6: "::1".parse().unwrap(),
+        }, peers);
+    }
+
+    #[test]
+    fn int4range() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Bounds {
+            lower: Option<i32>,
+            upper: Option<i32>,
+            lower_inclusive: bool,
+            upper_inclusive: bool,
+        }
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Slot {
+            span: Bounds,
+            open: Bounds,
+        }
+
+        let connection = setup_and_connect_to_db();
+
+        let results = connection.query(
+            "SELECT '[1,10)'::int4range AS span, '(,5]'::int4range AS open", &[]).unwrap();
+
+        let row = results.get(0);
+
+        let slot: Slot = super::from_row(row).unwrap();
+
+        assert_eq!(Slot {
+            span: Bounds { lower: Some(1), upper: Some(10), lower_inclusive: true, upper_inclusive: false },
+            // Postgres normalizes discrete ranges to exclusive upper bounds.
+            open: Bounds { lower: None, upper: Some(6), lower_inclusive: false, upper_inclusive: false },
+        }, slot);
+    }
+
+    #[cfg(feature = "with-chrono")]
+    #[test]
+    fn tsrange() {
+        use chrono::{NaiveDate, NaiveDateTime};
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Period {
+            lower: Option<NaiveDateTime>,
+            upper: Option<NaiveDateTime>,
+            empty: bool,
+        }
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Booking {
+            during: Period,
+        }
+
+        let connection = setup_and_connect_to_db();
+
+        let results = connection.query(
+            "SELECT '[2020-01-01 10:00, 2020-01-01 12:00)'::tsrange AS during", &[]).unwrap();
+
+        let row = results.get(0);
+
+        let booking: Booking = super::from_row(row).unwrap();
+
+        assert_eq!(Period {
+            lower: Some(NaiveDate::from_ymd(2020, 1, 1).and_hms(10, 0, 0)),
+            upper: Some(NaiveDate::from_ymd(2020, 1, 1).and_hms(12, 0, 0)),
+            empty: false,
+        }, booking.during);
+    }
+
+    #[test]
+    fn rows_reuse() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Buu {
+            id: i32,
+            name: String,
+        }
+
+        let connection = setup_and_connect_to_db();
+
+        let query = "SELECT id, 'Buu ' || id AS name FROM generate_series(1, 1000) AS id";
+
+        let fresh: Vec<Buu> = super::from_rows(connection.query(query, &[]).unwrap()).unwrap();
+        let reused: Vec<Buu> = super::from_rows_reuse(connection.query(query, &[]).unwrap()).unwrap();
+
+        assert_eq!(1000, reused.len());
+        assert_eq!(fresh, reused);
+    }
+
+    #[test]
+    fn column_index_cache() {
+        let connection = setup_and_connect_to_db();
+
+        let columns: Vec<String> = (0..50).map(|i| format!("{} AS c{}", i, i)).collect();
+        let query = format!("SELECT {}", columns.join(", "));
+
+        let results = connection.query(&*query, &[]).unwrap();
+
+        let row = results.get(0);
+
+        let linear = super::Deserializer::from_row_ref(&row);
+        let cached = super::Deserializer::from_row_ref(&row).with_column_index_cache();
+
+        for i in 0..50 {
+            let name = format!("c{}", i);
+            assert_eq!(Some(i), linear.column_index(&name));
+            assert_eq!(Some(i), cached.column_index(&name));
+        }
+        assert_eq!(None, cached.column_index("c50"));
+    }
+
+    #[cfg(feature = "async")]
+    #[test]
+    fn row_stream() {
+        use futures::{executor, stream, StreamExt};
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Buu {
+            id: i32,
+        }
+
+        let connection = setup_and_connect_to_db();
+
+        let results = connection.query("SELECT generate_series(1, 300) AS id", &[]).unwrap();
+
+        let rows = stream::iter(results.into_iter().map(Ok));
+        let buus: Vec<super::Result<Buu>> = executor::block_on(super::from_row_stream(rows).collect());
+
+        assert_eq!(300, buus.len());
+        for (i, buu) in buus.into_iter().enumerate() {
+            assert_eq!(Buu { id: i as i32 + 1 }, buu.unwrap());
+        }
+    }
+
+    #[cfg(feature = "with-chrono")]
+    #[test]
+    fn field_alias() {
+        use chrono::{NaiveDate, NaiveDateTime};
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Post {
+            #[serde(alias = "created_at")]
+            created: NaiveDateTime,
+        }
+
+        let connection = setup_and_connect_to_db();
+
+        let results = connection.query(
+            "SELECT '2020-01-01 10:00'::timestamp AS created_at", &[]).unwrap();
+
+        let row = results.get(0);
+
+        let post: Post = super::from_row_ref(&row).unwrap();
+
+        assert_eq!(NaiveDate::from_ymd(2020, 1, 1).and_hms(10, 0, 0), post.created);
+
+        let results = connection.query(
+            "SELECT '2020-01-01 10:00'::timestamp AS created", &[]).unwrap();
+
+        let row = results.get(0);
+
+        let post: Post = super::from_row_ref(&row).unwrap();
+
+        assert_eq!(NaiveDate::from_ymd(2020, 1, 1).and_hms(10, 0, 0), post.created);
+    }
+
+    #[test]
+    fn newtype_field() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct UserId(i32);
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct User {
+            id: UserId,
+            parent: Option<UserId>,
+        }
+
+        let connection = setup_and_connect_to_db();
+
+        let results = connection.query("SELECT 7::int4 AS id, NULL::int4 AS parent", &[]).unwrap();
+
+        let row = results.get(0);
+
+        let user: User = super::from_row(row).unwrap();
+
+        assert_eq!(User { id: UserId(7), parent: None }, user);
+    }
+
+    #[test]
+    fn money_cents() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Invoice {
+            total: i64,
+        }
+
+        let connection = setup_and_connect_to_db();
+
+        let results = connection.query("SELECT '$12.34'::money AS total", &[]).unwrap();
+
+        let row = results.get(0);
+
+        let invoice: Invoice = super::from_row(row).unwrap();
+
+        assert_eq!(Invoice { total: 1234 }, invoice);
+    }
+
+    #[test]
+    fn debug_columns() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Buu {
+            id: i32,
+            age: i32,
+        }
+
+        let connection = setup_and_connect_to_db();
+
+        let results = connection.query("SELECT 1::int4 AS id, 'Goku'::text AS name", &[]).unwrap();
+
+        let row = results.get(0);
+
+        let err = super::from_row_debug::<Buu>(row).unwrap_err();
+
+        assert_eq!(super::Error::WithColumns {
+            columns: String::from("id int4, name text"),
+            source: Box::new(super::Error::ColumnNotFound { name: String::from("age"), index: 1 }),
+        }, err);
+        assert_eq!("No column for field `age` at index 1 (columns: id int4, name text)",
+            err.to_string());
+    }
+
+    #[cfg(feature = "with-time")]
+    #[test]
+    fn time_crate() {
+        use time::{Date, OffsetDateTime, Time};
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Event {
+            #[serde(deserialize_with = "super::time_offset_date_time")]
+            created_at: OffsetDateTime,
+            #[serde(deserialize_with = "super::time_date")]
+            day: Date,
+            #[serde(deserialize_with = "super::time_time")]
+            starts: Time,
+        }
+
+        let connection = setup_and_connect_to_db();
+
+        let results = connection.query("SELECT '2021-06-15T12:30:00+02'::timestamptz AS created_at,
+            '2021-06-15'::date AS day,
+            '08:15:00'::time AS starts", &[])
+            .unwrap();
+
+        let row = results.get(0);
+
+        let event: Event = super::from_row(row).unwrap();
+
+        assert_eq!(OffsetDateTime::from_unix_timestamp(1_623_753_000), event.created_at);
+        assert_eq!(Date::try_from_ymd(2021, 6, 15).unwrap(), event.day);
+        assert_eq!(Time::try_from_hms(8, 15, 0).unwrap(), event.starts);
+
+        #[derive(Debug, Deserialize)]
+        struct Day {
+            #[serde(deserialize_with = "super::time_date")]
+            #[allow(dead_code)]
+            day: Date,
+        }
+
+        let results = connection.query("SELECT '200000-01-01'::date AS day", &[]).unwrap();
+
+        let row = results.get(0);
+
+        assert_eq!(
+            Err(super::Error::Message(String::from("value out of range for the time crate"))),
+            super::from_row::<Day>(row).map(|_| ()));
+
+        let results = connection.query("SELECT '5000000-01-01'::date AS day", &[]).unwrap();
+
+        let row = results.get(0);
+
+        assert_eq!(
+            Err(super::Error::InvalidType(String::from("day value out of range"))),
+            super::from_row::<Day>(row).map(|_| ()));
+
+        let results = connection.query("SELECT '294276-12-31'::timestamptz AS created_at,
+            '2021-06-15'::date AS day,
+            '08:15:00'::time AS starts", &[])
+            .unwrap();
+
+        let row = results.get(0);
+
+        assert_eq!(
+            Err(super::Error::InvalidType(String::from("created_at value out of range"))),
+            super::from_row::<Event>(row).map(|_| ()));
+    }
+
+    #[test]
+    fn borrowed_bytes() {
+        #[derive(Debug, Deserialize)]
+        struct Blob<'a> {
+            #[serde(borrow)]
+            data: &'a [u8],
+        }
+
+        let connection = setup_and_connect_to_db();
+
+        let results = connection.query(
+            "SELECT decode(repeat('ab', 1048576), 'hex') AS data", &[]).unwrap();
+
+        let row = results.get(0);
+
+        let blob: Blob = super::from_row_ref(&row).unwrap();
+        let data: &[u8] = row.get(0);
+
+        assert_eq!(1_048_576, blob.data.len());
+        // The field points into the row itself, so nothing was copied.
+        assert_eq!(data.as_ptr(), blob.data.as_ptr());
+    }
+
+    #[cfg(feature = "hstore")]
+    #[test]
+    fn hstore() {
+        use std::collections::HashMap;
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Tagged {
+            tags: HashMap<String, Option<String>>,
+        }
+
+        let connection = setup_and_connect_to_db();
+
+        connection.execute("CREATE EXTENSION IF NOT EXISTS hstore", &[]).unwrap();
+
+        let results = connection.query("SELECT 'a=>1, b=>NULL'::hstore AS tags", &[]).unwrap();
+
+        let row = results.get(0);
+
+        let tagged: Tagged = super::from_row(row).unwrap();
+
+        let mut tags = HashMap::new();
+        tags.insert(String::from("a"), Some(String::from("1")));
+        tags.insert(String::from("b"), None);
+        assert_eq!(Tagged { tags }, tagged);
+    }
+
+    #[test]
+    fn tag_column() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        #[serde(tag = "kind", rename_all = "lowercase")]
+        enum Shape {
+            Circle { radius: f64 },
+            Square { side: f64 },
+        }
+
+        let connection = setup_and_connect_to_db();
+
+        let results = connection.query("SELECT 'circle'::text AS kind,
+            1.5::float8 AS radius, NULL::float8 AS side
+            UNION ALL SELECT 'square', NULL, 2.0", &[]).unwrap();
+
+        let shapes: Vec<Shape> = super::from_rows(results).unwrap();
+
+        assert_eq!(vec![Shape::Circle { radius: 1.5 }, Shape::Square { side: 2.0 }], shapes);
+    }
+
+    #[test]
+    fn current_column() {
+        use tokio_postgres::types::Type;
+
+        let connection = setup_and_connect_to_db();
+
+        let results = connection.query("SELECT 1::int4 AS id", &[]).unwrap();
 
         let row = results.get(0);
 
-        let buu: Buu = super::from_row(row).unwrap();
+        let mut deserializer = super::Deserializer::from_row_ref(&row);
 
-        assert_eq!(None, buu.wants_candy);
-        assert_eq!(None, buu.width);
-        assert_eq!(None, buu.amount_eaten);
-        assert_eq!(None, buu.amount_want_to_eat);
-        assert_eq!(None, buu.speed);
-        assert_eq!(None, buu.weight);
-        assert_eq!(None, buu.catchphrase);
-        assert_eq!(None, buu.stomach_contents);
+        assert_eq!(Some("id"), deserializer.current_column_name());
+        assert_eq!(Some(&Type::INT4), deserializer.current_column_type());
 
-        connection.execute("DROP TABLE NullBuu", &[]).unwrap();
+        let _: (i32,) = ::serde::Deserialize::deserialize(&mut deserializer).unwrap();
+
+        assert_eq!(None, deserializer.current_column_name());
+        assert_eq!(None, deserializer.current_column_type());
     }
 
     #[test]
-    fn mispelled_field_name() {
+    fn varchar_enum() {
         #[derive(Debug, Deserialize, PartialEq)]
-        struct Buu {
-            wants_candie: bool,
+        enum Status {
+            #[serde(rename = "active")]
+            Active,
+            #[serde(rename = "disabled")]
+            Disabled,
+        }
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Account {
+            status: Status,
         }
 
         let connection = setup_and_connect_to_db();
 
-        connection.execute("CREATE TABLE IF NOT EXISTS SpellBuu (
-                    wants_candy BOOL NOT NULL
-        )", &[]).unwrap();
+        let results = connection.query("SELECT 'active'::varchar AS status", &[]).unwrap();
 
-        connection.execute("INSERT INTO SpellBuu (
-            wants_candy
-        ) VALUES ($1)",
-        &[&true]).unwrap();
+        let row = results.get(0);
 
-        let results = connection.query("SELECT wants_candy FROM SpellBuu", &[]).unwrap();
+        let account: Account = super::from_row(row).unwrap();
+
+        assert_eq!(Account { status: Status::Active }, account);
+
+        let results = connection.query("SELECT 1::int4 AS status", &[]).unwrap();
 
         let row = results.get(0);
 
         assert_eq!(
-            super::from_row::<Buu>(row),
-            Err(super::Error::Message(String::from("missing field `wants_candie`"))));
+            Err(super::Error::InvalidType(String::from("status expected text or enum column, found int4"))),
+            super::from_row::<Account>(row));
 
-        connection.execute("DROP TABLE SpellBuu", &[]).unwrap();
+        let results = connection.query("SELECT NULL::varchar AS status", &[]).unwrap();
+
+        let row = results.get(0);
+
+        assert_eq!(
+            Err(super::Error::InvalidType(String::from("status expected text or enum column, found NULL"))),
+            super::from_row::<Account>(row));
     }
 
     #[test]
-    fn missing_optional() {
+    fn null_as_default() {
+        use config::Config;
+
         #[derive(Debug, Deserialize, PartialEq)]
         struct Buu {
-            wants_candy: bool,
+            name: String,
+            age: i32,
+            power: i128,
         }
 
         let connection = setup_and_connect_to_db();
 
-        connection.execute("CREATE TABLE IF NOT EXISTS MiBuu (
-                    wants_candy BOOL
-        )", &[]).unwrap();
+        let results = connection.query(
+            "SELECT NULL::varchar AS name, NULL::int4 AS age, NULL::numeric AS power", &[]).unwrap();
 
-        connection.execute("INSERT INTO MiBuu (
-            wants_candy
-        ) VALUES ($1)",
-        &[&None::<bool>]).unwrap();
+        let row = results.get(0);
 
-        let results = connection.query("SELECT wants_candy FROM MiBuu", &[]).unwrap();
+        let mut deserializer = super::DeserializerBuilder::new()
+            .null_as_default(true)
+            .build_ref(&row);
+        let buu: Buu = ::serde::Deserialize::deserialize(&mut deserializer).unwrap();
+
+        assert_eq!(Buu { name: String::new(), age: 0, power: 0 }, buu);
+
+        assert!(super::from_row_ref::<Buu>(&row).is_err());
+
+        let config = Config::new().null_as_default(true);
+        let buu: Buu = super::from_row_with_config(row, config).unwrap();
+
+        assert_eq!(Buu { name: String::new(), age: 0, power: 0 }, buu);
+    }
+
+    #[test]
+    fn nested_arrays() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Matrix {
+            cells: Vec<Vec<i32>>,
+        }
+
+        let connection = setup_and_connect_to_db();
+
+        let results = connection.query("SELECT ARRAY[ARRAY[1,2],ARRAY[3,4]] AS cells", &[]).unwrap();
 
         let row = results.get(0);
 
-        assert_eq!(
-            super::from_row::<Buu>(row),
-            Err(super::Error::InvalidType(String::from("wants_candy Error(Conversion(WasNull))"))));
+        let matrix: Matrix = super::from_row(row).unwrap();
 
-        connection.execute("DROP TABLE MiBuu", &[]).unwrap();
+        assert_eq!(Matrix { cells: vec![vec![1, 2], vec![3, 4]] }, matrix);
     }
 
-    /*
-    use postgres_derive::FromSql;
+    #[cfg(feature = "macaddr")]
     #[test]
-    fn enums() {
+    fn eui48_macaddr() {
+        use eui48::MacAddress;
+
         #[derive(Debug, Deserialize, PartialEq)]
-        struct Goku {
-            hair: HairColour,
+        struct Device {
+            mac: MacAddress,
         }
 
-        #[derive(Debug, Deserialize, FromSql, PartialEq)]
-        #[postgres(name = "hair_colour")]
-        enum HairColour {
-            #[postgres(name = "black")]
-            Black,
-            #[postgres(name = "yellow")]
-            Yellow,
-            #[postgres(name = "blue")]
-            Blue,
+        let connection = setup_and_connect_to_db();
+
+        let results = connection.query("SELECT '08:00:2b:01:02:03'::macaddr AS mac", &[]).unwrap();
+
+        let row = results.get(0);
+
+        let device: Device = super::from_row(row).unwrap();
+
+        assert_eq!(MacAddress::new([0x08, 0x00, 0x2b, 0x01, 0x02, 0x03]), device.mac);
+    }
+
+    #[test]
+    fn oid_u32() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct PgType {
+            oid: u32,
         }
 
         let connection = setup_and_connect_to_db();
 
-        connection.execute("CREATE TYPE hair_colour as ENUM (
-            'black',
-            'yellow',
-            'blue'
-        )", &[]).unwrap();
+        let results = connection.query("SELECT oid FROM pg_type WHERE typname = 'int4'", &[]).unwrap();
 
-        connection.execute("CREATE TABLE Gokus (hair hair_colour)",
-        &[]).unwrap();
+        let row = results.get(0);
 
-        connection.execute("INSERT INTO Gokus VALUES ('black')", &[])
+        let pg_type: PgType = super::from_row(row).unwrap();
+
+        assert_eq!(PgType { oid: 23 }, pg_type);
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn flatten_extra_columns() {
+        use std::collections::HashMap;
+
+        use serde_json::Value;
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Buu {
+            id: i32,
+            #[serde(flatten)]
+            extra: HashMap<String, Value>,
+        }
+
+        let connection = setup_and_connect_to_db();
+
+        let results = connection.query("SELECT 1::int4 AS id, 'Goku'::text AS name, 9001::int4 AS power",
+            &[]).unwrap();
+
+        let row = results.get(0);
+
+        let buu: Buu = super::from_row(row).unwrap();
+
+        let mut extra = HashMap::new();
+        extra.insert(String::from("name"), Value::from("Goku"));
+        extra.insert(String::from("power"), Value::from(9001));
+        assert_eq!(Buu { id: 1, extra }, buu);
+    }
+
+    #[cfg(feature = "bit-vec")]
+    #[test]
+    fn bit_string() {
+        use bit_vec::BitVec;
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Flags {
+            bits: BitVec,
+            bools: Vec<bool>,
+        }
+
+        let connection = setup_and_connect_to_db();
+
+        let results = connection.query("SELECT B'101'::bit(3) AS bits, B'101'::varbit AS bools", &[])
             .unwrap();
 
-        let results = connection.query("SELECT * FROM Gokus", &[])
+        let row = results.get(0);
+
+        let flags: Flags = super::from_row(row).unwrap();
+
+        assert_eq!(3, flags.bits.len());
+        assert_eq!(vec![true, false, true], flags.bits.iter().collect::<Vec<_>>());
+        assert_eq!(vec![true, false, true], flags.bools);
+    }
+
+    #[test]
+    fn generic_map() {
+        use std::collections::BTreeMap;
+
+        use config::Config;
+
+        let connection = setup_and_connect_to_db();
+
+        let results = connection.query("SELECT 1 AS a, 2 AS b, 3::int8 AS c", &[]).unwrap();
+
+        let row = results.get(0);
+
+        let config = Config::new().coerce_integers(true);
+        let map: BTreeMap<String, i32> = super::from_row_with_config(row, config).unwrap();
+
+        let expected: BTreeMap<_, _> = vec![
+            (String::from("a"), 1),
+            (String::from("b"), 2),
+            (String::from("c"), 3),
+        ].into_iter().collect();
+        assert_eq!(expected, map);
+
+        let results = connection.query("SELECT 1 AS a, 'x'::text AS b", &[]).unwrap();
+
+        let row = results.get(0);
+
+        match super::from_row::<BTreeMap<String, i32>>(row) {
+            Err(super::Error::Column { ref name, .. }) => assert_eq!("b", name),
+            other => panic!("expected a column error, found {:?}", other),
+        }
+    }
+
+    #[test]
+    fn numeric_128_bit() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Ids {
+            signed: i128,
+            unsigned: u128,
+        }
+
+        let connection = setup_and_connect_to_db();
+
+        let results = connection.query("SELECT -123456789012345678901234567890::numeric AS signed,
+            123456789012345678901234567890::numeric AS unsigned", &[]).unwrap();
+
+        let row = results.get(0);
+
+        let ids: Ids = super::from_row(row).unwrap();
+
+        assert_eq!(Ids {
+            signed: -123_456_789_012_345_678_901_234_567_890,
+            unsigned: 123_456_789_012_345_678_901_234_567_890,
+        }, ids);
+
+        let results = connection.query("SELECT 1.5::numeric AS signed, 1::numeric AS unsigned", &[])
             .unwrap();
 
         let row = results.get(0);
 
-        let goku: Goku = super::from_row(row).unwrap();
+        assert!(super::from_row::<Ids>(row).is_err());
+    }
 
-        assert_eq!(HairColour::Black, goku.hair);
+    #[test]
+    fn interval_std_duration() {
+        use std::time::Duration;
 
-        connection.execute("DROP TABLE Gokus", &[]).unwrap();
-        connection.execute("DROP TYPE hair_colour", &[]).unwrap();
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Job {
+            timeout: Duration,
+        }
+
+        let connection = setup_and_connect_to_db();
+
+        let results = connection.query("SELECT '3600 seconds'::interval AS timeout", &[]).unwrap();
+
+        let row = results.get(0);
+
+        let job: Job = super::from_row(row).unwrap();
+
+        assert_eq!(Job { timeout: Duration::from_secs(3600) }, job);
+
+        let results = connection.query("SELECT '1 month'::interval AS timeout", &[]).unwrap();
+
+        let row = results.get(0);
+
+        assert!(super::from_row::<Job>(row).is_err());
+    }
+
+    #[test]
+    fn remaining_columns() {
+        use serde::de::SeqAccess;
+
+        let connection = setup_and_connect_to_db();
+
+        let results = connection.query("SELECT 1::int4 AS id, 'Goku'::text AS name", &[]).unwrap();
+
+        let row = results.get(0);
+
+        let mut deserializer = super::Deserializer::from_row_ref(&row);
+
+        assert_eq!(2, deserializer.remaining_columns());
+        assert_eq!(Some(1), deserializer.next_element::<i32>().unwrap());
+        assert_eq!(1, deserializer.remaining_columns());
+        assert_eq!(Some(String::from("Goku")), deserializer.next_element::<String>().unwrap());
+        assert_eq!(0, deserializer.remaining_columns());
+    }
+
+    #[test]
+    fn point_coordinates() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Point {
+            x: f64,
+            y: f64,
+        }
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Location {
+            coordinates: (f64, f64),
+            point: Point,
+        }
+
+        let connection = setup_and_connect_to_db();
+
+        let results = connection.query("SELECT '(1.5, 2.5)'::point AS coordinates,
+            '(1.5, 2.5)'::point AS point", &[]).unwrap();
+
+        let row = results.get(0);
+
+        let location: Location = super::from_row(row).unwrap();
+
+        assert_eq!(Location {
+            coordinates: (1.5, 2.5),
+            point: Point { x: 1.5, y: 2.5 },
+        }, location);
+    }
+
+    #[test]
+    fn params_round_trip() {
+        use serde_derive::Serialize;
+        use tokio_postgres::types::ToSql;
+
+        use ser;
+
+        #[derive(Debug, Deserialize, PartialEq, Serialize)]
+        struct Buu {
+            wants_candy: bool,
+            width: i16,
+            amount_eaten: i32,
+            amount_want_to_eat: i64,
+            speed: f32,
+            weight: f64,
+            catchphrase: String,
+            stomach_contents: Vec<u8>,
+            nickname: Option<String>,
+        }
+
+        let connection = setup_and_connect_to_db();
+
+        connection.execute("CREATE TABLE IF NOT EXISTS ParamBuu (
+                    wants_candy BOOL NOT NULL,
+                    width SMALLINT NOT NULL,
+                    amount_eaten INT NOT NULL,
+                    amount_want_to_eat BIGINT NOT NULL,
+                    speed REAL NOT NULL,
+                    weight DOUBLE PRECISION NOT NULL,
+                    catchphrase VARCHAR NOT NULL,
+                    stomach_contents BYTEA NOT NULL,
+                    nickname VARCHAR
+        )", &[]).unwrap();
+
+        let buu = Buu {
+            wants_candy: true,
+            width: 20,
+            amount_eaten: 1000,
+            amount_want_to_eat: 1000_000,
+            speed: 99.99,
+            weight: 9999.9999,
+            catchphrase: String::from("Woo Woo"),
+            stomach_contents: vec![1, 2, 3, 4, 5, 6],
+            nickname: None,
+        };
+
+        let columns = ser::column_names(&buu).unwrap();
+        let params = ser::to_params(&buu).unwrap();
+        assert_eq!(columns.len(), params.len());
+
+        let placeholders: Vec<_> = (1..=params.len()).map(|i| format!("${}", i)).collect();
+        let query = format!("INSERT INTO ParamBuu ({}) VALUES ({})",
+            columns.join(", "), placeholders.join(", "));
+        let params: Vec<_> = params.iter().map(|param| &**param as &(dyn ToSql + Sync)).collect();
+        connection.execute(&query, &params).unwrap();
+
+        let results = connection.query("SELECT * FROM ParamBuu", &[]).unwrap();
+
+        let row = results.get(0);
+
+        assert_eq!(buu, super::from_row(row).unwrap());
+
+        connection.execute("DROP TABLE ParamBuu", &[]).unwrap();
+    }
+
+    #[test]
+    fn identifier() {
+        use std::fmt;
+
+        use serde::de::{self, Deserializer, Visitor};
+
+        #[derive(Debug, PartialEq)]
+        struct Identifier(String);
+
+        impl<'de> de::Deserialize<'de> for Identifier {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                struct IdentifierVisitor;
+
+                impl<'de> Visitor<'de> for IdentifierVisitor {
+                    type Value = Identifier;
+
+                    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                        f.write_str("an identifier")
+                    }
+
+                    fn visit_str<E: de::Error>(self, v: &str) -> Result<Identifier, E> {
+                        Ok(Identifier(v.to_owned()))
+                    }
+                }
+
+                deserializer.deserialize_identifier(IdentifierVisitor)
+            }
+        }
+
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Event {
+            kind: Identifier,
+        }
+
+        let connection = setup_and_connect_to_db();
+
+        let results = connection.query("SELECT 'created'::text AS kind", &[]).unwrap();
+
+        let row = results.get(0);
+
+        let event: Event = super::from_row_ref(&row).unwrap();
+        assert_eq!(Event { kind: Identifier(String::from("created")) }, event);
+
+        // The whole row's identifier is its current column's name.
+        let column: Identifier = super::from_row_ref(&row).unwrap();
+        assert_eq!(Identifier(String::from("kind")), column);
+    }
+
+    #[test]
+    fn scalar() {
+        let connection = setup_and_connect_to_db();
+
+        let results = connection.query("SELECT count(*) FROM (VALUES (1), (2), (3)) AS t", &[])
+            .unwrap();
+        let count: i64 = super::from_row_scalar(results.get(0)).unwrap();
+        assert_eq!(3, count);
+
+        let results = connection.query("SELECT 'Goku'::text", &[]).unwrap();
+        let name: String = super::from_row_scalar(results.get(0)).unwrap();
+        assert_eq!("Goku", name);
+
+        let results = connection.query("SELECT true", &[]).unwrap();
+        let flag: bool = super::from_row_scalar(results.get(0)).unwrap();
+        assert!(flag);
     }
-    */
 }
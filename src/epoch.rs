@@ -0,0 +1,57 @@
+//! Postgres dates and times as microseconds since the Unix epoch.
+use tokio_postgres::types::Type;
+
+use error::{Error, Result};
+
+/// Name of the newtype struct the `de::time_*` functions ask for, to read a
+/// date or timestamp as microseconds since the Unix epoch, or a `time` as
+/// microseconds since midnight.
+pub const UNIX_MICROS: &str = "$serde_postgres::epoch::UnixMicros";
+
+/// Microseconds from the Unix epoch to 2000-01-01, which Postgres counts
+/// from.
+const POSTGRES_EPOCH: i64 = 946_684_800_000_000;
+
+const MICROS_PER_DAY: i64 = 86_400_000_000;
+
+/// Decode a `timestamp`, `timestamptz`, `date` or `time` into microseconds,
+/// `None` for any other type.
+pub fn to_unix_micros(ty: &Type, raw: &[u8]) -> Result<Option<i64>> {
+    match *ty {
+        Type::TIME => read_i64(raw).map(Some),
+        Type::TIMESTAMP | Type::TIMESTAMPTZ => match read_i64(raw)? {
+            ::std::i64::MAX | ::std::i64::MIN => Err(infinite()),
+            micros => micros.checked_add(POSTGRES_EPOCH).map(Some).ok_or_else(out_of_range),
+        },
+        Type::DATE => match read_i32(raw)? {
+            ::std::i32::MAX | ::std::i32::MIN => Err(infinite()),
+            days => i64::from(days).checked_mul(MICROS_PER_DAY)
+                .and_then(|micros| micros.checked_add(POSTGRES_EPOCH))
+                .map(Some)
+                .ok_or_else(out_of_range),
+        },
+        _ => Ok(None),
+    }
+}
+
+fn infinite() -> Error {
+    Error::InvalidType(String::from("value is infinite"))
+}
+
+fn out_of_range() -> Error {
+    Error::InvalidType(String::from("value out of range"))
+}
+
+fn read_i64(raw: &[u8]) -> Result<i64> {
+    match raw.len() {
+        8 => Ok(i64::from_be_bytes([raw[0], raw[1], raw[2], raw[3], raw[4], raw[5], raw[6], raw[7]])),
+        len => Err(Error::InvalidType(format!("expected 8 bytes, found {}", len))),
+    }
+}
+
+fn read_i32(raw: &[u8]) -> Result<i32> {
+    match raw.len() {
+        4 => Ok(i32::from_be_bytes([raw[0], raw[1], raw[2], raw[3]])),
+        len => Err(Error::InvalidType(format!("expected 4 bytes, found {}", len))),
+    }
+}
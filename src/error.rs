@@ -1,14 +1,18 @@
 //! When serializing or deserializing from Postgres rows goes wrong.
 use std::{fmt, error};
+use std::sync::Arc;
 
 use serde::{de, ser};
+use tokio_postgres;
 
 /// Alias for a `Result` with the error type `serde_postgres::Error`.
 pub type Result<T> = ::std::result::Result<T, Error>;
 
 /// This type represents all possible error that can occur when deserializing
 /// postgres rows.
-#[derive(Clone, Debug, PartialEq)]
+///
+/// Errors from `tokio_postgres` compare equal if they display the same.
+#[derive(Clone, Debug)]
 pub enum Error {
     /// A custom defined error occured. Typically coming from `serde`.
     Message(String),
@@ -18,6 +22,69 @@ pub enum Error {
     InvalidType(String),
     /// Rust data structure contained a type unsupported by `serde_postgres`.
     UnsupportedType,
+    /// Row's column had a Postgres type, named here, that can't be read
+    /// without knowing the Rust type it's read into.
+    UnsupportedColumnType(String),
+    /// Deserialization was cancelled through a `CancellationToken`.
+    Cancelled,
+    /// A column's value couldn't be converted into the Rust type, e.g.
+    /// because it was `NULL` or of another type.
+    Column {
+        /// The column's name.
+        name: String,
+        /// The error `tokio_postgres` returned, or the one decoding the
+        /// column's transformed value.
+        source: Arc<dyn error::Error + Send + Sync>,
+    },
+    /// The row has no column for a field.
+    ColumnNotFound {
+        /// The field's name, empty if the column was looked up by position
+        /// alone.
+        name: String,
+        /// The field's position, which is also the column's for structs
+        /// read by position.
+        index: usize,
+    },
+    /// An error from `tokio_postgres` unrelated to any one column, e.g. from
+    /// running the query the rows came from.
+    Postgres(Arc<tokio_postgres::Error>),
+    /// Another error, with the names and types of the row's columns added
+    /// by `from_row_debug`.
+    WithColumns {
+        /// The row's columns, e.g. `id int4, name text`.
+        columns: String,
+        /// The error deserializing the row.
+        source: Box<Error>,
+    },
+}
+
+impl PartialEq for Error {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (&Error::Message(ref a), &Error::Message(ref b)) => a == b,
+            (&Error::UnknownField, &Error::UnknownField) => true,
+            (&Error::InvalidType(ref a), &Error::InvalidType(ref b)) => a == b,
+            (&Error::UnsupportedType, &Error::UnsupportedType) => true,
+            (&Error::UnsupportedColumnType(ref a), &Error::UnsupportedColumnType(ref b)) => a == b,
+            (&Error::Cancelled, &Error::Cancelled) => true,
+            (&Error::Column { name: ref a, source: ref a_source },
+             &Error::Column { name: ref b, source: ref b_source }) =>
+                a == b && a_source.to_string() == b_source.to_string(),
+            (&Error::ColumnNotFound { name: ref a, index: a_index },
+             &Error::ColumnNotFound { name: ref b, index: b_index }) => a == b && a_index == b_index,
+            (&Error::Postgres(ref a), &Error::Postgres(ref b)) => a.to_string() == b.to_string(),
+            (&Error::WithColumns { columns: ref a, source: ref a_source },
+             &Error::WithColumns { columns: ref b, source: ref b_source }) =>
+                a == b && a_source == b_source,
+            _ => false,
+        }
+    }
+}
+
+impl From<tokio_postgres::Error> for Error {
+    fn from(error: tokio_postgres::Error) -> Self {
+        Error::Postgres(Arc::new(error))
+    }
 }
 
 impl de::Error for Error {
@@ -34,7 +101,18 @@ impl ser::Error for Error {
 
 impl fmt::Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        f.write_str(error::Error::description(self))
+        match *self {
+            Error::UnsupportedColumnType(ref ty) => write!(f, "Type {} unsupported", ty),
+            Error::Column { ref name, ref source } => write!(f, "{}: {}", name, source),
+            Error::ColumnNotFound { ref name, index } if name.is_empty() =>
+                write!(f, "No column at index {}", index),
+            Error::ColumnNotFound { ref name, index } =>
+                write!(f, "No column for field `{}` at index {}", name, index),
+            Error::Postgres(ref source) => fmt::Display::fmt(source, f),
+            Error::WithColumns { ref columns, ref source } =>
+                write!(f, "{} (columns: {})", source, columns),
+            _ => f.write_str(error::Error::description(self)),
+        }
     }
 }
 
@@ -45,6 +123,21 @@ impl error::Error for Error {
             Error::UnknownField => "Unknown field",
             Error::InvalidType(_) => "Invalid type",
             Error::UnsupportedType => "Type unsupported",
+            Error::UnsupportedColumnType(_) => "Type unsupported",
+            Error::Cancelled => "Cancelled",
+            Error::Column { .. } => "Column unreadable",
+            Error::ColumnNotFound { .. } => "Column not found",
+            Error::Postgres(_) => "Postgres error",
+            Error::WithColumns { .. } => "Row unreadable",
+        }
+    }
+
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match *self {
+            Error::Column { ref source, .. } => Some(&**source),
+            Error::Postgres(ref source) => Some(&**source),
+            Error::WithColumns { ref source, .. } => Some(&**source),
+            _ => None,
         }
     }
 }
@@ -0,0 +1,117 @@
+//! Deserialize Postgres geometric types into `geo-types` geometries.
+use serde::de::{
+    self,
+    IntoDeserializer,
+    Visitor,
+    value::{MapDeserializer, SeqDeserializer},
+};
+use tokio_postgres::types::Type;
+use geo_types::{Coordinate, LineString, Point};
+
+use error::{Error, Result};
+use raw;
+
+/// A decoded geometric column, deserialized in the same shape as the
+/// `geo-types` geometry it corresponds to.
+pub enum Geometry {
+    /// A `point`, as the `Coordinate` wrapped by a `Point`.
+    Coordinate(Coordinate<f64>),
+    /// A `path`, as a `LineString`.
+    LineString(Vec<Coordinate<f64>>),
+    /// A `polygon`, as a `Polygon` without interior rings.
+    Polygon(Vec<Coordinate<f64>>),
+    /// The interior rings of a `Polygon`.
+    Rings(Vec<Vec<Coordinate<f64>>>),
+}
+
+impl Geometry {
+    /// Decode a value of type `ty`, or `None` if `ty` isn't geometric.
+    pub fn from_sql(ty: &Type, raw: Option<&[u8]>) -> Result<Option<Self>> {
+        let geometry = match *ty {
+            Type::POINT => Geometry::Coordinate(raw::decode::<Point<f64>>(ty, raw)?.0),
+            Type::PATH => Geometry::LineString(raw::decode::<LineString<f64>>(ty, raw)?.0),
+            Type::POLYGON => Geometry::Polygon(polygon_from_sql(raw)?),
+            _ => return Ok(None),
+        };
+
+        Ok(Some(geometry))
+    }
+}
+
+/// Decode a `polygon` into its closed exterior ring.
+fn polygon_from_sql(raw: Option<&[u8]>) -> Result<Vec<Coordinate<f64>>> {
+    let raw = raw.ok_or_else(|| Error::InvalidType(String::from("polygon was NULL")))?;
+    let invalid = || Error::InvalidType(String::from("invalid polygon value"));
+
+    if raw.len() < 4 {
+        return Err(invalid());
+    }
+    let (count, mut points) = raw.split_at(4);
+    let count = i32::from_be_bytes([count[0], count[1], count[2], count[3]]) as usize;
+    if points.len() != count * 16 {
+        return Err(invalid());
+    }
+
+    let mut ring = Vec::with_capacity(count + 1);
+    while !points.is_empty() {
+        let (point, rest) = points.split_at(16);
+        let mut x = [0; 8];
+        let mut y = [0; 8];
+        x.copy_from_slice(&point[..8]);
+        y.copy_from_slice(&point[8..]);
+        ring.push(Coordinate {
+            x: f64::from_bits(u64::from_be_bytes(x)),
+            y: f64::from_bits(u64::from_be_bytes(y)),
+        });
+        points = rest;
+    }
+
+    // `geo-types` polygons repeat their first point at the end of the ring.
+    if let (Some(&first), Some(&last)) = (ring.first(), ring.last()) {
+        if first != last {
+            ring.push(first);
+        }
+    }
+
+    Ok(ring)
+}
+
+impl<'de> de::Deserializer<'de> for Geometry {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        match self {
+            Geometry::Coordinate(c) => visitor.visit_map(
+                MapDeserializer::new(vec![("x", c.x), ("y", c.y)].into_iter())),
+            Geometry::LineString(coords) => visitor.visit_seq(
+                SeqDeserializer::new(coords.into_iter().map(Geometry::Coordinate))),
+            Geometry::Polygon(exterior) => visitor.visit_map(
+                MapDeserializer::new(vec![
+                    ("exterior", Geometry::LineString(exterior)),
+                    ("interiors", Geometry::Rings(Vec::new())),
+                ].into_iter())),
+            Geometry::Rings(rings) => visitor.visit_seq(
+                SeqDeserializer::new(rings.into_iter().map(Geometry::LineString))),
+        }
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(self, _: &str, visitor: V)
+        -> Result<V::Value>
+    {
+        visitor.visit_newtype_struct(self)
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 u8 u16 u32 u64 f32 f64 char str string bytes
+        byte_buf option unit unit_struct seq tuple tuple_struct map struct
+        enum identifier ignored_any
+    }
+}
+
+impl<'de> IntoDeserializer<'de, Error> for Geometry {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self {
+        self
+    }
+}
@@ -0,0 +1,83 @@
+//! Deserialize `hstore` values as a map of their entries.
+use std::collections::hash_map;
+use std::collections::HashMap;
+
+use serde::de::{self, DeserializeSeed, IntoDeserializer, MapAccess, Visitor};
+use tokio_postgres::types::Type;
+
+use error::{Error, Result};
+
+/// Whether `ty` is the `hstore` extension type, which has no fixed OID.
+pub fn is_hstore(ty: &Type) -> bool {
+    ty.name() == "hstore"
+}
+
+/// Deserialize an `hstore`'s entries as a map of key to value, `NULL` values
+/// read as `None`.
+pub fn deserialize<'de, V>(hstore: HashMap<String, Option<String>>, visitor: V)
+    -> Result<V::Value>
+    where V: Visitor<'de>
+{
+    visitor.visit_map(Entries {
+        entries: hstore.into_iter(),
+        value: None,
+    })
+}
+
+struct Entries {
+    entries: hash_map::IntoIter<String, Option<String>>,
+    /// The value of the entry whose key was handed out last.
+    value: Option<Option<String>>,
+}
+
+impl<'de> MapAccess<'de> for Entries {
+    type Error = Error;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(&mut self, seed: K) -> Result<Option<K::Value>> {
+        match self.entries.next() {
+            Some((key, value)) => {
+                self.value = Some(value);
+                seed.deserialize(key.into_deserializer()).map(Some)
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value> {
+        match self.value.take() {
+            Some(value) => seed.deserialize(Value(value)),
+            None => Err(Error::Message(String::from("hstore value requested before its key"))),
+        }
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.entries.len())
+    }
+}
+
+/// A single `hstore` value, `None` if `NULL`.
+struct Value(Option<String>);
+
+impl<'de> de::Deserializer<'de> for Value {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        match self.0 {
+            Some(value) => visitor.visit_string(value),
+            None => visitor.visit_none(),
+        }
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        match self.0 {
+            Some(value) => visitor.visit_some(IntoDeserializer::<Error>::into_deserializer(value)),
+            None => visitor.visit_none(),
+        }
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 u8 u16 u32 u64 f32 f64 char str string bytes
+        byte_buf unit unit_struct newtype_struct seq tuple tuple_struct map
+        struct enum identifier ignored_any
+    }
+}
@@ -0,0 +1,86 @@
+//! Decoding of Postgres `interval` values.
+use std::time::Duration;
+
+use error::{Error, Result};
+
+/// Name of the newtype struct `de::chrono_duration` asks for, to read an
+/// `interval` as its length in microseconds.
+pub const DURATION: &str = "$serde_postgres::interval::Duration";
+
+/// A decoded `interval`, split into the same parts Postgres keeps apart
+/// because they aren't fixed multiples of each other.
+pub struct Interval {
+    /// Whole months, which have no fixed number of days.
+    pub months: i32,
+    /// Whole days, which have no fixed number of seconds across DST changes.
+    pub days: i32,
+    /// Everything smaller than a day.
+    pub microseconds: i64,
+}
+
+impl Interval {
+    /// Parse the binary representation of an `interval`.
+    pub fn from_sql(raw: &[u8]) -> Result<Self> {
+        if raw.len() != 16 {
+            return Err(Error::InvalidType(String::from("invalid interval value")));
+        }
+
+        let mut microseconds = [0; 8];
+        microseconds.copy_from_slice(&raw[..8]);
+
+        Ok(Interval {
+            microseconds: i64::from_be_bytes(microseconds),
+            days: i32::from_be_bytes([raw[8], raw[9], raw[10], raw[11]]),
+            months: i32::from_be_bytes([raw[12], raw[13], raw[14], raw[15]]),
+        })
+    }
+
+    /// The interval's length in microseconds. Intervals with months have no
+    /// fixed length, so those are an error.
+    pub fn to_microseconds(&self) -> Result<i64> {
+        if self.months != 0 {
+            return Err(Error::InvalidType(String::from(
+                "interval with months has no fixed length")));
+        }
+
+        i64::from(self.days).checked_mul(86_400_000_000)
+            .and_then(|days| days.checked_add(self.microseconds))
+            .ok_or_else(|| Error::InvalidType(String::from("interval out of range")))
+    }
+
+    /// The interval as a `std::time::Duration`, which can't have months or
+    /// be negative.
+    pub fn to_std(&self) -> Result<Duration> {
+        let microseconds = self.to_microseconds()?;
+        if microseconds < 0 {
+            return Err(Error::InvalidType(String::from(
+                "negative interval can't be a Duration")));
+        }
+
+        Ok(Duration::from_micros(microseconds as u64))
+    }
+
+    /// Format as an ISO 8601 duration such as `P1Y2M3DT4H5M6.789S`, to
+    /// millisecond precision. Negative intervals have no ISO 8601 form.
+    #[cfg(feature = "with-iso8601")]
+    pub fn to_iso8601(&self) -> Result<String> {
+        if self.months < 0 || self.days < 0 || self.microseconds < 0 {
+            return Err(Error::InvalidType(String::from(
+                "negative interval can't be an ISO 8601 duration")));
+        }
+
+        let mut iso = format!("P{}Y{}M{}D", self.months / 12, self.months % 12, self.days);
+
+        let millis = self.microseconds / 1000;
+        if millis > 0 {
+            let seconds = millis / 1000 % 60;
+            iso.push_str(&format!("T{}H{}M{}", millis / 3_600_000, millis / 60_000 % 60, seconds));
+            if millis % 1000 > 0 {
+                iso.push_str(&format!(".{:03}", millis % 1000));
+            }
+            iso.push('S');
+        }
+
+        Ok(iso)
+    }
+}
@@ -1,7 +1,8 @@
 //! # Serde Postgres
 //!
 //! Easily deserialize rows from [`postgres`](//docs.rs/postgres) into
-//! arbitrary structs. (Only deserialization is supported).
+//! arbitrary structs, and serialize structs into query parameters with
+//! `ser::to_params`.
 //!
 //! ```rust,no_run
 //! extern crate serde;
@@ -47,14 +48,86 @@
 //! ```
 #![deny(missing_docs)]
 
-extern crate serde;
+#[cfg(feature = "bit-vec")] extern crate bit_vec;
+extern crate bytes;
+#[cfg(feature = "with-chrono")] extern crate chrono;
+#[cfg(feature = "macaddr")] extern crate eui48;
+#[cfg(feature = "async")] extern crate futures;
+#[cfg(feature = "with-geo-types")] extern crate geo_types;
+#[cfg(feature = "with-ipnet")] extern crate ipnet;
+#[cfg(feature = "with-num-rational")] extern crate num_bigint;
+#[cfg(feature = "with-num-rational")] extern crate num_rational;
+#[cfg(feature = "with-rust-decimal")] extern crate rust_decimal;
+#[macro_use] extern crate serde;
+#[cfg(feature = "json")] extern crate serde_json;
+#[cfg(feature = "with-time")] extern crate time;
 extern crate tokio_postgres;
+#[cfg(feature = "with-uuid")] extern crate uuid;
 // extern crate postgres_derive;
 
+#[cfg(test)] extern crate serde_bytes;
 #[cfg(test)] extern crate serde_derive;
 
+mod array;
+#[cfg(feature = "bit-vec")]
+mod bits;
+pub mod config;
 pub mod de;
+mod epoch;
 pub mod error;
+#[cfg(feature = "with-geo-types")]
+mod geo;
+#[cfg(feature = "hstore")]
+mod hstore;
+mod interval;
+mod lsn;
+mod money;
+mod network;
+mod numeric;
+mod range;
+#[cfg(feature = "with-num-rational")]
+mod rational;
+mod raw;
+pub mod schema;
+pub mod ser;
+mod suggest;
+#[cfg(feature = "with-chrono")]
+mod timestamp;
+mod typed;
+mod wkt;
 
-pub use de::{from_row, from_rows, Deserializer};
+pub use array::RawArray;
+pub use config::{ColumnCase, Config, UnknownFallback};
+pub use de::{
+    from_row,
+    from_row_debug,
+    from_row_ordered,
+    from_row_ref,
+    from_row_scalar,
+    from_row_strict,
+    from_row_with_config,
+    from_row_with_field_mapper,
+    from_row_with_overrides,
+    from_row_with_transforms,
+    from_rows,
+    from_rows_cancellable,
+    from_rows_iter,
+    from_rows_partial,
+    from_rows_reuse,
+    from_rows_with_buffer_pool,
+    from_rows_with_metrics,
+    row_has_columns,
+    BufferPool,
+    CachedDeserializer,
+    CancellationToken,
+    Deserializer,
+    DeserializerBuilder,
+    Transform,
+};
+#[cfg(feature = "async")]
+pub use de::from_row_stream;
+pub use raw::RawValue;
 pub use error::{Error, Result};
+pub use schema::{validate_schema, SchemaMismatch};
+pub use ser::to_params;
+pub use typed::Typed;
@@ -0,0 +1,27 @@
+//! Decoding of Postgres `pg_lsn` values.
+use std::fmt;
+
+use error::{Error, Result};
+
+/// A decoded `pg_lsn`, a position in the write-ahead log.
+pub struct Lsn(pub u64);
+
+impl Lsn {
+    /// Parse the binary representation of a `pg_lsn`.
+    pub fn from_sql(raw: &[u8]) -> Result<Self> {
+        if raw.len() != 8 {
+            return Err(Error::InvalidType(String::from("invalid pg_lsn value")));
+        }
+
+        let mut lsn = [0; 8];
+        lsn.copy_from_slice(raw);
+        Ok(Lsn(u64::from_be_bytes(lsn)))
+    }
+}
+
+/// Formats the way Postgres does, e.g. `16/B374D848`.
+impl fmt::Display for Lsn {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:X}/{:X}", self.0 >> 32, self.0 & 0xffff_ffff)
+    }
+}
@@ -0,0 +1,38 @@
+//! Decoding of Postgres `money` values.
+use std::fmt;
+
+use error::{Error, Result};
+
+/// A decoded `money` value, in hundredths of the currency unit.
+///
+/// Postgres sends `money` as a bare integer whose scale comes from the
+/// server's `lc_monetary`; the common scale of 2 is assumed here.
+pub struct Money(pub i64);
+
+impl Money {
+    /// Parse the binary representation of a `money`.
+    pub fn from_sql(raw: &[u8]) -> Result<Self> {
+        if raw.len() != 8 {
+            return Err(Error::InvalidType(String::from("invalid money value")));
+        }
+
+        let mut cents = [0; 8];
+        cents.copy_from_slice(raw);
+        Ok(Money(i64::from_be_bytes(cents)))
+    }
+
+    /// The amount in whole currency units, losing precision for large ones.
+    pub fn to_f64(&self) -> f64 {
+        self.0 as f64 / 100.0
+    }
+}
+
+/// Formats as a plain decimal such as `-1234.56`, without the currency
+/// symbol and grouping of Postgres' locale-dependent text output.
+impl fmt::Display for Money {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let sign = if self.0 < 0 { "-" } else { "" };
+        let cents = (self.0 as i128).abs();
+        write!(f, "{}{}.{:02}", sign, cents / 100, cents % 100)
+    }
+}
@@ -0,0 +1,60 @@
+//! Decoding of Postgres `inet` and `cidr` values.
+use std::fmt;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+use error::{Error, Result};
+
+const FAMILY_INET: u8 = 2;
+const FAMILY_INET6: u8 = 3;
+
+/// A decoded `inet` or `cidr` value.
+pub struct Network {
+    /// The host or network address.
+    pub addr: IpAddr,
+    /// The number of bits in the netmask.
+    pub netmask: u8,
+    /// Whether the value is a `cidr` rather than an `inet`.
+    pub is_cidr: bool,
+}
+
+impl Network {
+    /// Parse the binary representation of an `inet` or `cidr`.
+    pub fn from_sql(raw: &[u8]) -> Result<Self> {
+        let invalid = || Error::InvalidType(String::from("invalid inet or cidr value"));
+
+        if raw.len() < 4 {
+            return Err(invalid());
+        }
+        let (header, octets) = raw.split_at(4);
+
+        let addr = match (header[0], octets.len()) {
+            (FAMILY_INET, 4) =>
+                IpAddr::V4(Ipv4Addr::new(octets[0], octets[1], octets[2], octets[3])),
+            (FAMILY_INET6, 16) => {
+                let mut v6 = [0; 16];
+                v6.copy_from_slice(octets);
+                IpAddr::V6(Ipv6Addr::from(v6))
+            }
+            _ => return Err(invalid()),
+        };
+
+        Ok(Network { addr, netmask: header[1], is_cidr: header[2] != 0 })
+    }
+}
+
+/// Formats like Postgres does: a `cidr` always shows its netmask, an
+/// `inet` only when the netmask doesn't span the whole address.
+impl fmt::Display for Network {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let host = match self.addr {
+            IpAddr::V4(_) => 32,
+            IpAddr::V6(_) => 128,
+        };
+
+        if self.is_cidr || self.netmask != host {
+            write!(f, "{}/{}", self.addr, self.netmask)
+        } else {
+            write!(f, "{}", self.addr)
+        }
+    }
+}
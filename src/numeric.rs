@@ -0,0 +1,162 @@
+//! Decoding of the binary representation of Postgres `numeric` values.
+use std::convert::TryFrom;
+use std::fmt;
+
+#[cfg(feature = "with-num-rational")]
+use num_bigint::BigInt;
+#[cfg(feature = "with-num-rational")]
+use num_rational::BigRational;
+
+use error::{Error, Result};
+
+const SIGN_NEGATIVE: u16 = 0x4000;
+const SIGN_NAN: u16 = 0xC000;
+
+/// A decoded, non-NaN `numeric` value.
+pub struct Numeric {
+    /// Whether the value is negative.
+    pub negative: bool,
+    /// The power of 10000 that the first digit is multiplied by.
+    pub weight: i16,
+    /// Base 10000 digits, most significant first.
+    pub digits: Vec<u16>,
+    /// The number of decimal digits after the point, trailing zeros
+    /// included.
+    pub scale: u16,
+}
+
+impl Numeric {
+    /// Parse the binary representation of a `numeric`.
+    pub fn from_sql(mut buf: &[u8]) -> Result<Self> {
+        let count = read_u16(&mut buf)?;
+        let weight = read_u16(&mut buf)? as i16;
+        let sign = read_u16(&mut buf)?;
+        let scale = read_u16(&mut buf)?;
+
+        if sign == SIGN_NAN {
+            return Err(Error::InvalidType(String::from("numeric is NaN")));
+        }
+
+        let mut digits = Vec::with_capacity(count as usize);
+        for _ in 0..count {
+            digits.push(read_u16(&mut buf)?);
+        }
+
+        Ok(Numeric { negative: sign == SIGN_NEGATIVE, weight, digits, scale })
+    }
+
+    /// The value as an `i128`, if it's a whole number in range.
+    pub fn to_i128(&self) -> Result<i128> {
+        let magnitude = self.magnitude("i128")?;
+        match (self.negative, i128::try_from(magnitude)) {
+            (false, Ok(n)) => Ok(n),
+            (true, Ok(n)) => Ok(-n),
+            (true, Err(_)) if magnitude == 1 << 127 => Ok(i128::min_value()),
+            _ => Err(out_of_range("i128")),
+        }
+    }
+
+    /// The value as a `u128`, if it's a whole number in range.
+    pub fn to_u128(&self) -> Result<u128> {
+        let magnitude = self.magnitude("u128")?;
+        if self.negative && magnitude != 0 {
+            return Err(out_of_range("u128"));
+        }
+        Ok(magnitude)
+    }
+
+    /// The absolute value, if it's a whole number that fits a `u128`, with
+    /// `ty` the type to name in range errors.
+    fn magnitude(&self, ty: &str) -> Result<u128> {
+        let mut magnitude: u128 = 0;
+        for (i, &digit) in self.digits.iter().enumerate() {
+            if i32::from(self.weight) < i as i32 {
+                if digit != 0 {
+                    return Err(Error::InvalidType(String::from("numeric has a fractional part")));
+                }
+                continue;
+            }
+            magnitude = magnitude.checked_mul(10000)
+                .and_then(|n| n.checked_add(u128::from(digit)))
+                .ok_or_else(|| out_of_range(ty))?;
+        }
+
+        // Trailing zero digits of the integral part aren't stored.
+        for _ in self.digits.len() as i32..=i32::from(self.weight) {
+            magnitude = magnitude.checked_mul(10000).ok_or_else(|| out_of_range(ty))?;
+        }
+
+        Ok(magnitude)
+    }
+
+    /// The exact value as a reduced fraction.
+    #[cfg(feature = "with-num-rational")]
+    pub fn to_rational(&self) -> BigRational {
+        let base = BigInt::from(10000);
+        let mut numer = self.digits.iter()
+            .fold(BigInt::from(0), |n, &digit| n * &base + BigInt::from(digit));
+        let mut denom = BigInt::from(1);
+
+        let exponent = i32::from(self.weight) + 1 - self.digits.len() as i32;
+        for _ in 0..exponent.abs() {
+            if exponent > 0 {
+                numer = numer * &base;
+            } else {
+                denom = denom * &base;
+            }
+        }
+
+        if self.negative {
+            numer = -numer;
+        }
+
+        BigRational::new(numer, denom)
+    }
+}
+
+/// Formats the value as Postgres does, e.g. `0.100` for a `numeric(4, 3)`.
+impl fmt::Display for Numeric {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let digit = |i: i32| if i < 0 { 0 } else { self.digits.get(i as usize).cloned().unwrap_or(0) };
+
+        if self.negative {
+            f.write_str("-")?;
+        }
+
+        if self.weight < 0 {
+            f.write_str("0")?;
+        } else {
+            write!(f, "{}", digit(0))?;
+            for i in 1..=i32::from(self.weight) {
+                write!(f, "{:04}", digit(i))?;
+            }
+        }
+
+        if self.scale > 0 {
+            let scale = usize::from(self.scale);
+            let mut fraction = String::with_capacity(scale + 4);
+            let mut i = i32::from(self.weight) + 1;
+            while fraction.len() < scale {
+                fraction.push_str(&format!("{:04}", digit(i)));
+                i += 1;
+            }
+            fraction.truncate(scale);
+            write!(f, ".{}", fraction)?;
+        }
+
+        Ok(())
+    }
+}
+
+fn out_of_range(ty: &str) -> Error {
+    Error::InvalidType(format!("numeric out of range for {}", ty))
+}
+
+fn read_u16(buf: &mut &[u8]) -> Result<u16> {
+    if buf.len() < 2 {
+        return Err(Error::InvalidType(String::from("numeric value is truncated")));
+    }
+    let (head, rest) = buf.split_at(2);
+    *buf = rest;
+    Ok(u16::from_be_bytes([head[0], head[1]]))
+}
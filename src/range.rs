@@ -0,0 +1,128 @@
+//! Deserialize Postgres ranges as a map of their bounds.
+use serde::de::{DeserializeSeed, IntoDeserializer, MapAccess, Visitor};
+use tokio_postgres::types::{Kind, Type};
+
+use array::Element;
+use config::Config;
+use error::{Error, Result};
+
+const RANGE_EMPTY: u8 = 0x01;
+const RANGE_LB_INC: u8 = 0x02;
+const RANGE_UB_INC: u8 = 0x04;
+const RANGE_LB_INF: u8 = 0x08;
+const RANGE_UB_INF: u8 = 0x10;
+
+/// The keys a range is handed out under, in order.
+const KEYS: [&str; 5] = ["lower", "upper", "lower_inclusive", "upper_inclusive", "empty"];
+
+/// The type of a range's bounds, if `ty` is a range type.
+pub fn member(ty: &Type) -> Option<&Type> {
+    match *ty.kind() {
+        Kind::Range(ref member) => Some(member),
+        _ => None,
+    }
+}
+
+/// A range's bounds, borrowed from its binary representation.
+struct Range<'a> {
+    /// The lower bound's raw value, `None` if unbounded or empty.
+    lower: Option<&'a [u8]>,
+    /// The upper bound's raw value, `None` if unbounded or empty.
+    upper: Option<&'a [u8]>,
+    lower_inclusive: bool,
+    upper_inclusive: bool,
+    empty: bool,
+}
+
+impl<'a> Range<'a> {
+    /// Parse the binary representation of a range.
+    fn from_sql(buf: &'a [u8]) -> Result<Self> {
+        let (&flags, mut buf) = buf.split_first().ok_or_else(truncated)?;
+        let empty = flags & RANGE_EMPTY != 0;
+
+        let lower = if empty || flags & RANGE_LB_INF != 0 {
+            None
+        } else {
+            Some(read_bound(&mut buf)?)
+        };
+        let upper = if empty || flags & RANGE_UB_INF != 0 {
+            None
+        } else {
+            Some(read_bound(&mut buf)?)
+        };
+
+        Ok(Range {
+            lower,
+            upper,
+            lower_inclusive: flags & RANGE_LB_INC != 0,
+            upper_inclusive: flags & RANGE_UB_INC != 0,
+            empty,
+        })
+    }
+}
+
+fn read_bound<'a>(buf: &mut &'a [u8]) -> Result<&'a [u8]> {
+    if buf.len() < 4 {
+        return Err(truncated());
+    }
+    let (head, rest) = buf.split_at(4);
+    let len = i32::from_be_bytes([head[0], head[1], head[2], head[3]]) as usize;
+    if rest.len() < len {
+        return Err(truncated());
+    }
+    let (bound, rest) = rest.split_at(len);
+    *buf = rest;
+    Ok(bound)
+}
+
+fn truncated() -> Error {
+    Error::InvalidType(String::from("range value is truncated"))
+}
+
+/// Deserialize the raw value of a range with bounds of type `member` as a
+/// map with the keys `lower`, `upper`, `lower_inclusive`, `upper_inclusive`
+/// and `empty`.
+pub fn deserialize<'de, V>(member: &Type, raw: &[u8], config: &Config, visitor: V)
+    -> Result<V::Value>
+    where V: Visitor<'de>
+{
+    visitor.visit_map(Bounds {
+        member,
+        config,
+        range: Range::from_sql(raw)?,
+        index: 0,
+    })
+}
+
+struct Bounds<'a> {
+    member: &'a Type,
+    config: &'a Config,
+    range: Range<'a>,
+    index: usize,
+}
+
+impl<'de, 'a> MapAccess<'de> for Bounds<'a> {
+    type Error = Error;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(&mut self, seed: K) -> Result<Option<K::Value>> {
+        match KEYS.get(self.index) {
+            Some(key) => seed.deserialize(key.into_deserializer()).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value> {
+        self.index += 1;
+        match self.index {
+            1 => seed.deserialize(Element::new(self.member, self.range.lower, self.config)),
+            2 => seed.deserialize(Element::new(self.member, self.range.upper, self.config)),
+            3 => seed.deserialize(self.range.lower_inclusive.into_deserializer()),
+            4 => seed.deserialize(self.range.upper_inclusive.into_deserializer()),
+            _ => seed.deserialize(self.range.empty.into_deserializer()),
+        }
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(KEYS.len() - self.index)
+    }
+}
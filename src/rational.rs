@@ -0,0 +1,65 @@
+//! Deserialize `numeric` values into `num-rational`'s `BigRational`.
+use num_bigint::{BigInt, Sign};
+use num_rational::BigRational;
+use serde::de::{self, IntoDeserializer, Visitor, value::SeqDeserializer};
+
+use error::{Error, Result};
+
+/// A `BigRational`, or a part of one, in the shape of its serde
+/// representation: a `(numer, denom)` tuple of `BigInt`s, which are each
+/// a `(sign, digits)` tuple of an `i8` and little-endian `u32` digits.
+pub enum Rational {
+    /// The whole fraction.
+    Ratio(BigRational),
+    /// The numerator or denominator.
+    Int(BigInt),
+    /// The sign of a `BigInt`.
+    Sign(i8),
+    /// The magnitude of a `BigInt`.
+    Digits(Vec<u32>),
+}
+
+impl<'de> de::Deserializer<'de> for Rational {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        match self {
+            Rational::Ratio(ratio) => visitor.visit_seq(SeqDeserializer::new(vec![
+                Rational::Int(ratio.numer().clone()),
+                Rational::Int(ratio.denom().clone()),
+            ].into_iter())),
+            Rational::Int(int) => {
+                let (sign, bytes) = int.to_bytes_le();
+                let sign = match sign {
+                    Sign::Minus => -1,
+                    Sign::NoSign => 0,
+                    Sign::Plus => 1,
+                };
+                let digits = bytes.chunks(4)
+                    .map(|chunk| chunk.iter().rev().fold(0, |digit, &byte| digit << 8 | u32::from(byte)))
+                    .collect();
+
+                visitor.visit_seq(SeqDeserializer::new(vec![
+                    Rational::Sign(sign),
+                    Rational::Digits(digits),
+                ].into_iter()))
+            }
+            Rational::Sign(sign) => visitor.visit_i8(sign),
+            Rational::Digits(digits) => visitor.visit_seq(SeqDeserializer::new(digits.into_iter())),
+        }
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 u8 u16 u32 u64 f32 f64 char str string bytes
+        byte_buf option unit unit_struct newtype_struct seq tuple
+        tuple_struct map struct enum identifier ignored_any
+    }
+}
+
+impl<'de> IntoDeserializer<'de, Error> for Rational {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self {
+        self
+    }
+}
@@ -0,0 +1,97 @@
+//! Access to the undecoded binary representation of column values.
+use std::any;
+use std::error::Error as StdError;
+
+use serde::de::Visitor;
+use tokio_postgres::types::{FromSql, Type};
+
+use error::{Error, Result};
+
+/// The binary wire representation of a single, non-NULL column value.
+///
+/// Used for Postgres types that `tokio_postgres` has no `FromSql`
+/// implementation for, so that they can be decoded here instead.
+pub struct Raw<'a>(pub &'a [u8]);
+
+impl<'a> FromSql<'a> for Raw<'a> {
+    fn from_sql(_: &Type, raw: &'a [u8])
+        -> ::std::result::Result<Self, Box<dyn StdError + Sync + Send>>
+    {
+        Ok(Raw(raw))
+    }
+
+    fn accepts(_: &Type) -> bool {
+        true
+    }
+}
+
+/// An owned, undecoded column value handed to a column transform.
+#[derive(Clone, Debug)]
+pub struct RawValue {
+    ty: Type,
+    bytes: Option<Vec<u8>>,
+}
+
+impl RawValue {
+    /// Create a value of type `ty` from its binary representation, `None`
+    /// for `NULL`.
+    pub fn new(ty: Type, bytes: Option<Vec<u8>>) -> Self {
+        RawValue { ty, bytes }
+    }
+
+    /// The Postgres type of the value.
+    pub fn ty(&self) -> &Type {
+        &self.ty
+    }
+
+    /// The binary representation of the value, `None` if `NULL`.
+    pub fn bytes(&self) -> Option<&[u8]> {
+        self.bytes.as_ref().map(|bytes| &bytes[..])
+    }
+
+    /// Replace the binary representation, keeping the type.
+    pub fn with_bytes(self, bytes: Option<Vec<u8>>) -> Self {
+        RawValue { bytes, ..self }
+    }
+}
+
+/// Decode a raw value of type `ty`, the way `Row::try_get` would.
+pub fn decode<'a, T: FromSql<'a>>(ty: &Type, raw: Option<&'a [u8]>) -> Result<T> {
+    if !T::accepts(ty) {
+        return Err(Error::InvalidType(format!(
+            "cannot convert {} to {}", ty, any::type_name::<T>())));
+    }
+
+    T::from_sql_nullable(ty, raw).map_err(|e| Error::InvalidType(e.to_string()))
+}
+
+/// Whether `ty` is one of Postgres' integer types.
+pub fn is_integer(ty: &Type) -> bool {
+    match *ty {
+        Type::INT2 | Type::INT4 | Type::INT8 => true,
+        _ => false,
+    }
+}
+
+/// Decode a raw integer of type `ty`, widened to `i64`.
+pub fn integer(ty: &Type, raw: Option<&[u8]>) -> Result<i64> {
+    match *ty {
+        Type::INT2 => decode::<i16>(ty, raw).map(i64::from),
+        Type::INT4 => decode::<i32>(ty, raw).map(i64::from),
+        Type::INT8 => decode(ty, raw),
+        _ => Err(Error::InvalidType(format!("{} is not an integer type", ty))),
+    }
+}
+
+/// Visit a raw integer of type `ty` as its matching Rust type, leaving it to
+/// the visitor to check that the value fits the type it was asked for.
+pub fn visit_integer<'de, V: Visitor<'de>>(ty: &Type, raw: Option<&[u8]>, visitor: V)
+    -> Result<V::Value>
+{
+    match *ty {
+        Type::INT2 => visitor.visit_i16(decode(ty, raw)?),
+        Type::INT4 => visitor.visit_i32(decode(ty, raw)?),
+        Type::INT8 => visitor.visit_i64(decode(ty, raw)?),
+        _ => Err(Error::InvalidType(format!("{} is not an integer type", ty))),
+    }
+}
@@ -0,0 +1,314 @@
+//! Check a result set's columns against a struct before deserializing it.
+use std::fmt;
+use std::iter;
+
+use serde::de::{self, Deserialize, DeserializeSeed, IntoDeserializer, MapAccess, Visitor};
+use serde::de::value::{MapDeserializer, SeqDeserializer};
+use tokio_postgres::Column;
+use tokio_postgres::types::Type;
+
+use error::{Error, Result};
+
+/// A way in which a result set doesn't fit the struct it's checked against.
+#[derive(Clone, Debug, PartialEq)]
+pub enum SchemaMismatch {
+    /// The struct has a field with no column of the same name.
+    MissingColumn(String),
+    /// A column's type can't be read into the field of the same name.
+    TypeMismatch {
+        /// The column's name.
+        column: String,
+        /// The Rust type the field asked for.
+        expected: &'static str,
+        /// The column's Postgres type.
+        found: Type,
+    },
+}
+
+impl fmt::Display for SchemaMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            SchemaMismatch::MissingColumn(ref field) =>
+                write!(f, "no column for field `{}`", field),
+            SchemaMismatch::TypeMismatch { ref column, expected, ref found } =>
+                write!(f, "column `{}` of type {} can't be read as {}", column, found, expected),
+        }
+    }
+}
+
+/// Check that `columns` can be deserialized into `T` without reading any
+/// rows, returning every mismatch found.
+///
+/// `T`'s fields are found by letting it deserialize itself from a stand-in
+/// for the row, one column at a time. Only the types of `bool`, integer and
+/// float fields are checked; other fields just need a column to exist,
+/// unless `T` can do without them, e.g. `Option`s and `#[serde(default)]`
+/// fields. Targets that aren't structs, such as maps, have nothing to check.
+pub fn validate_schema<'de, T>(columns: &[Column]) -> ::std::result::Result<(), Vec<SchemaMismatch>>
+    where T: Deserialize<'de>,
+{
+    let mut probe = Probe { column: None, fields: None, mismatches: Vec::new() };
+    // The probe bails out with an error once it knows the fields, or for
+    // fields it can't make up a value for; only what it records matters.
+    let _ = T::deserialize(&mut probe);
+
+    let fields = match probe.fields {
+        Some(fields) => fields,
+        None => return Ok(()),
+    };
+
+    let missing: Vec<_> = fields.iter().cloned()
+        .filter(|field| !columns.iter().any(|column| column.name() == *field))
+        .collect();
+    for field in required::<T>(fields, &missing) {
+        probe.mismatches.push(SchemaMismatch::MissingColumn(field.to_owned()));
+    }
+
+    for column in columns.iter().filter(|column| fields.contains(&column.name())) {
+        probe.column = Some(column);
+        let _ = T::deserialize(&mut probe);
+    }
+
+    if probe.mismatches.is_empty() {
+        Ok(())
+    } else {
+        Err(probe.mismatches)
+    }
+}
+
+/// The fields of `missing` that `T` can't be deserialized without.
+///
+/// `T` is handed a placeholder for every other field, and asks for the first
+/// missing field it needs, if any. That one is handed a placeholder too, and
+/// so on. If a placeholder can't be made up for a field, the fields not
+/// asked for yet are all taken to be required.
+fn required<'de, T>(fields: &[&'static str], missing: &[&'static str]) -> Vec<&'static str>
+    where T: Deserialize<'de>,
+{
+    let mut given: Vec<_> = fields.iter().cloned().filter(|field| !missing.contains(field)).collect();
+    let mut required = Vec::new();
+
+    loop {
+        let asked = match T::deserialize(Placeholders { fields: &given }) {
+            Ok(_) => return required,
+            Err(Error::Message(ref msg)) => msg.strip_prefix("missing field `")
+                .and_then(|rest| rest.strip_suffix('`'))
+                .and_then(|field| missing.iter().cloned().find(|missing| *missing == field))
+                .filter(|field| !given.contains(field)),
+            Err(_) => None,
+        };
+
+        match asked {
+            Some(field) => {
+                required.push(field);
+                given.push(field);
+            }
+            None => {
+                required.extend(missing.iter().filter(|field| !given.contains(field)));
+                return required;
+            }
+        }
+    }
+}
+
+/// Stands in for a row with a placeholder value for each of `fields`.
+struct Placeholders<'a> {
+    fields: &'a [&'static str],
+}
+
+impl<'de, 'a> de::Deserializer<'de> for Placeholders<'a> {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, _: V) -> Result<V::Value> {
+        Err(stop())
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(self, _: &'static str, _: &'static [&'static str], visitor: V)
+        -> Result<V::Value>
+    {
+        visitor.visit_map(Entries { fields: self.fields, index: 0, mismatches: Vec::new() })
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 u8 u16 u32 u64 f32 f64 char str string bytes
+        byte_buf option unit unit_struct newtype_struct seq tuple tuple_struct
+        map enum identifier ignored_any
+    }
+}
+
+/// A map of each of `fields` to a placeholder value.
+struct Entries<'a> {
+    fields: &'a [&'static str],
+    index: usize,
+    /// Nothing is checked, so nothing ends up here.
+    mismatches: Vec<SchemaMismatch>,
+}
+
+impl<'de, 'a> MapAccess<'de> for Entries<'a> {
+    type Error = Error;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(&mut self, seed: K) -> Result<Option<K::Value>> {
+        match self.fields.get(self.index) {
+            Some(field) => seed.deserialize(field.into_deserializer()).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value> {
+        self.index += 1;
+        seed.deserialize(Value { column: None, mismatches: &mut self.mismatches })
+    }
+}
+
+/// Stands in for a row containing at most the one column being checked.
+struct Probe<'a> {
+    column: Option<&'a Column>,
+    fields: Option<&'static [&'static str]>,
+    mismatches: Vec<SchemaMismatch>,
+}
+
+fn stop() -> Error {
+    Error::Message(String::from("schema probe stopped"))
+}
+
+impl<'de, 'a, 'b> de::Deserializer<'de> for &'b mut Probe<'a> {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, _: V) -> Result<V::Value> {
+        Err(stop())
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(self, _: &'static str, fields: &'static [&'static str], visitor: V)
+        -> Result<V::Value>
+    {
+        self.fields = Some(fields);
+        match self.column {
+            Some(column) => visitor.visit_map(Single {
+                column,
+                mismatches: &mut self.mismatches,
+                done: false,
+            }),
+            None => Err(stop()),
+        }
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 u8 u16 u32 u64 f32 f64 char str string bytes
+        byte_buf option unit unit_struct newtype_struct seq tuple tuple_struct
+        map enum identifier ignored_any
+    }
+}
+
+/// A map with just the column being checked.
+struct Single<'a> {
+    column: &'a Column,
+    mismatches: &'a mut Vec<SchemaMismatch>,
+    done: bool,
+}
+
+impl<'de, 'a> MapAccess<'de> for Single<'a> {
+    type Error = Error;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(&mut self, seed: K) -> Result<Option<K::Value>> {
+        if self.done {
+            return Ok(None);
+        }
+
+        seed.deserialize(self.column.name().into_deserializer()).map(Some)
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value> {
+        self.done = true;
+        seed.deserialize(Value { column: Some(self.column), mismatches: &mut *self.mismatches })
+    }
+}
+
+/// Stands in for a column's value, recording whether its type fits the Rust
+/// type asked for and handing out a placeholder of that type. Without a
+/// column, only the placeholder is handed out.
+struct Value<'a> {
+    column: Option<&'a Column>,
+    mismatches: &'a mut Vec<SchemaMismatch>,
+}
+
+impl<'a> Value<'a> {
+    fn check(self, expected: &'static str, accepted: &[Type]) {
+        let column = match self.column {
+            Some(column) => column,
+            None => return,
+        };
+
+        if !accepted.contains(column.type_()) {
+            self.mismatches.push(SchemaMismatch::TypeMismatch {
+                column: column.name().to_owned(),
+                expected,
+                found: column.type_().clone(),
+            });
+        }
+    }
+}
+
+macro_rules! check_value {
+    ($($fn_name:ident => $visit:ident($value:expr), $ty:ident, [$($accepted:ident),*];)*) => {
+        $(
+            fn $fn_name<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+                self.check(stringify!($ty), &[$(Type::$accepted),*]);
+                visitor.$visit($value)
+            }
+        )*
+    }
+}
+
+impl<'de, 'a> de::Deserializer<'de> for Value<'a> {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, _: V) -> Result<V::Value> {
+        Err(stop())
+    }
+
+    check_value! {
+        deserialize_bool => visit_bool(false), bool, [BOOL];
+        deserialize_i8 => visit_i8(0), i8, [CHAR];
+        deserialize_i16 => visit_i16(0), i16, [INT2];
+        deserialize_i32 => visit_i32(0), i32, [INT4];
+        deserialize_i64 => visit_i64(0), i64, [INT2, INT4, INT8];
+        deserialize_u32 => visit_u32(0), u32, [OID];
+        deserialize_f32 => visit_f32(0.0), f32, [FLOAT4];
+        deserialize_f64 => visit_f64(0.0), f64, [FLOAT4, FLOAT8];
+    }
+
+    fn deserialize_str<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_str("")
+    }
+
+    fn deserialize_string<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_str("")
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(self, _: &'static str, visitor: V)
+        -> Result<V::Value>
+    {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_ignored_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_seq(SeqDeserializer::<_, Error>::new(iter::empty::<()>()))
+    }
+
+    fn deserialize_map<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_map(MapDeserializer::<_, Error>::new(iter::empty::<((), ())>()))
+    }
+
+    forward_to_deserialize_any! {
+        u8 u16 u64 char bytes byte_buf unit unit_struct tuple tuple_struct
+        struct enum identifier
+    }
+}
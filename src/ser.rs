@@ -0,0 +1,370 @@
+//! Serialize Rust structs into query parameters.
+//!
+//! Every field of the struct becomes one parameter, in order, so a struct
+//! can be inserted with a query built from its column names:
+//!
+//! ```rust,ignore
+//! let columns = serde_postgres::ser::column_names(&buu)?;
+//! let params = serde_postgres::ser::to_params(&buu)?;
+//! let placeholders: Vec<_> = (1..=params.len()).map(|i| format!("${}", i)).collect();
+//! let query = format!("INSERT INTO Buu ({}) VALUES ({})",
+//!     columns.join(", "), placeholders.join(", "));
+//! let params: Vec<_> = params.iter().map(|param| &**param as &(dyn ToSql + Sync)).collect();
+//! connection.execute(&*query, &params)?;
+//! ```
+use std::convert::TryFrom;
+use std::error::Error as StdError;
+
+use bytes::BytesMut;
+use serde::ser::{self, Impossible, Serialize};
+use tokio_postgres::types::{IsNull, ToSql, Type};
+
+use error::{Error, Result};
+
+/// A query parameter, as `tokio_postgres` takes them.
+pub type Param = Box<dyn ToSql + Sync>;
+
+/// Serialize the fields of the struct `value` into query parameters, in the
+/// order the fields are declared.
+///
+/// Unsigned integers are widened to the next larger Postgres integer type,
+/// as Postgres has no unsigned ones, and `u64`s must fit a `bigint`. `None`
+/// is `NULL` for a column of any type. Unit variants are their name, as
+/// text, and sequences of `u8`, e.g. a `Vec<u8>`, are a `bytea`. Nested
+/// structs, other sequences and maps aren't supported.
+pub fn to_params<T: Serialize>(value: &T) -> Result<Vec<Param>> {
+    serialize(value).map(|fields| fields.params)
+}
+
+/// The names of the fields of the struct `value`, in the same order as the
+/// parameters from `to_params`.
+pub fn column_names<T: Serialize>(value: &T) -> Result<Vec<&'static str>> {
+    serialize(value).map(|fields| fields.names)
+}
+
+fn serialize<T: Serialize>(value: &T) -> Result<Fields> {
+    value.serialize(StructSerializer)
+}
+
+/// `NULL`, which unlike `Option::None` can be sent for a column of any type.
+#[derive(Debug)]
+struct Null;
+
+impl ToSql for Null {
+    fn to_sql(&self, _: &Type, _: &mut BytesMut)
+        -> ::std::result::Result<IsNull, Box<dyn StdError + Sync + Send>>
+    {
+        Ok(IsNull::Yes)
+    }
+
+    fn accepts(_: &Type) -> bool {
+        true
+    }
+
+    ::tokio_postgres::types::to_sql_checked!();
+}
+
+macro_rules! unsupported {
+    ($($fn_name:ident($($arg:ty),*) -> $ok:ty;)*) => {
+        $(
+            fn $fn_name(self, $(_: $arg),*) -> Result<$ok> {
+                Err(Error::UnsupportedType)
+            }
+        )*
+    }
+}
+
+/// Serializes the top-level struct, the only type with named fields to
+/// become columns.
+struct StructSerializer;
+
+impl ser::Serializer for StructSerializer {
+    type Ok = Fields;
+    type Error = Error;
+    type SerializeSeq = Impossible<Fields, Error>;
+    type SerializeTuple = Impossible<Fields, Error>;
+    type SerializeTupleStruct = Impossible<Fields, Error>;
+    type SerializeTupleVariant = Impossible<Fields, Error>;
+    type SerializeMap = Impossible<Fields, Error>;
+    type SerializeStruct = Fields;
+    type SerializeStructVariant = Impossible<Fields, Error>;
+
+    fn serialize_struct(self, _: &'static str, len: usize) -> Result<Fields> {
+        Ok(Fields {
+            names: Vec::with_capacity(len),
+            params: Vec::with_capacity(len),
+        })
+    }
+
+    fn serialize_newtype_struct<T: ?Sized + Serialize>(self, _: &'static str, value: &T)
+        -> Result<Fields>
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_some<T: ?Sized + Serialize>(self, _: &T) -> Result<Fields> {
+        Err(Error::UnsupportedType)
+    }
+
+    fn serialize_newtype_variant<T: ?Sized + Serialize>(self, _: &'static str, _: u32, _: &'static str, _: &T)
+        -> Result<Fields>
+    {
+        Err(Error::UnsupportedType)
+    }
+
+    unsupported! {
+        serialize_bool(bool) -> Fields;
+        serialize_i8(i8) -> Fields;
+        serialize_i16(i16) -> Fields;
+        serialize_i32(i32) -> Fields;
+        serialize_i64(i64) -> Fields;
+        serialize_u8(u8) -> Fields;
+        serialize_u16(u16) -> Fields;
+        serialize_u32(u32) -> Fields;
+        serialize_u64(u64) -> Fields;
+        serialize_f32(f32) -> Fields;
+        serialize_f64(f64) -> Fields;
+        serialize_char(char) -> Fields;
+        serialize_str(&str) -> Fields;
+        serialize_bytes(&[u8]) -> Fields;
+        serialize_none() -> Fields;
+        serialize_unit() -> Fields;
+        serialize_unit_struct(&'static str) -> Fields;
+        serialize_unit_variant(&'static str, u32, &'static str) -> Fields;
+        serialize_seq(Option<usize>) -> Impossible<Fields, Error>;
+        serialize_tuple(usize) -> Impossible<Fields, Error>;
+        serialize_tuple_struct(&'static str, usize) -> Impossible<Fields, Error>;
+        serialize_tuple_variant(&'static str, u32, &'static str, usize) -> Impossible<Fields, Error>;
+        serialize_map(Option<usize>) -> Impossible<Fields, Error>;
+        serialize_struct_variant(&'static str, u32, &'static str, usize) -> Impossible<Fields, Error>;
+    }
+}
+
+/// A struct's fields, serialized so far.
+struct Fields {
+    names: Vec<&'static str>,
+    params: Vec<Param>,
+}
+
+impl ser::SerializeStruct for Fields {
+    type Ok = Fields;
+    type Error = Error;
+
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, key: &'static str, value: &T)
+        -> Result<()>
+    {
+        let param = value.serialize(ParamSerializer)
+            .map_err(|err| match err {
+                Error::UnsupportedType => Error::Message(format!("field `{}` can't be a parameter", key)),
+                err => err,
+            })?;
+        self.names.push(key);
+        self.params.push(param);
+        Ok(())
+    }
+
+    fn end(self) -> Result<Fields> {
+        Ok(self)
+    }
+}
+
+/// Serializes a single field into a parameter.
+struct ParamSerializer;
+
+impl ser::Serializer for ParamSerializer {
+    type Ok = Param;
+    type Error = Error;
+    type SerializeSeq = Bytes;
+    type SerializeTuple = Impossible<Param, Error>;
+    type SerializeTupleStruct = Impossible<Param, Error>;
+    type SerializeTupleVariant = Impossible<Param, Error>;
+    type SerializeMap = Impossible<Param, Error>;
+    type SerializeStruct = Impossible<Param, Error>;
+    type SerializeStructVariant = Impossible<Param, Error>;
+
+    fn serialize_bool(self, v: bool) -> Result<Param> {
+        Ok(Box::new(v))
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<Param> {
+        // An `i8` is a `"char"` to `tokio_postgres`, not a number.
+        Ok(Box::new(i16::from(v)))
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<Param> {
+        Ok(Box::new(v))
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<Param> {
+        Ok(Box::new(v))
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<Param> {
+        Ok(Box::new(v))
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<Param> {
+        Ok(Box::new(i16::from(v)))
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<Param> {
+        Ok(Box::new(i32::from(v)))
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<Param> {
+        Ok(Box::new(i64::from(v)))
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<Param> {
+        i64::try_from(v)
+            .map(|v| Box::new(v) as Param)
+            .map_err(|_| Error::InvalidType(String::from("u64 out of range for bigint")))
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<Param> {
+        Ok(Box::new(v))
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<Param> {
+        Ok(Box::new(v))
+    }
+
+    fn serialize_char(self, v: char) -> Result<Param> {
+        Ok(Box::new(v.to_string()))
+    }
+
+    fn serialize_str(self, v: &str) -> Result<Param> {
+        Ok(Box::new(v.to_owned()))
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<Param> {
+        Ok(Box::new(v.to_vec()))
+    }
+
+    fn serialize_none(self) -> Result<Param> {
+        Ok(Box::new(Null))
+    }
+
+    fn serialize_some<T: ?Sized + Serialize>(self, value: &T) -> Result<Param> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<Param> {
+        Ok(Box::new(Null))
+    }
+
+    fn serialize_unit_struct(self, _: &'static str) -> Result<Param> {
+        Ok(Box::new(Null))
+    }
+
+    fn serialize_unit_variant(self, _: &'static str, _: u32, variant: &'static str)
+        -> Result<Param>
+    {
+        Ok(Box::new(variant.to_owned()))
+    }
+
+    fn serialize_newtype_struct<T: ?Sized + Serialize>(self, _: &'static str, value: &T)
+        -> Result<Param>
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: ?Sized + Serialize>(self, _: &'static str, _: u32, _: &'static str, _: &T)
+        -> Result<Param>
+    {
+        Err(Error::UnsupportedType)
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<Bytes> {
+        Ok(Bytes(Vec::with_capacity(len.unwrap_or(0))))
+    }
+
+    unsupported! {
+        serialize_tuple(usize) -> Impossible<Param, Error>;
+        serialize_tuple_struct(&'static str, usize) -> Impossible<Param, Error>;
+        serialize_tuple_variant(&'static str, u32, &'static str, usize) -> Impossible<Param, Error>;
+        serialize_map(Option<usize>) -> Impossible<Param, Error>;
+        serialize_struct(&'static str, usize) -> Impossible<Param, Error>;
+        serialize_struct_variant(&'static str, u32, &'static str, usize) -> Impossible<Param, Error>;
+    }
+}
+
+/// A sequence of bytes serialized so far, sent as a `bytea`.
+struct Bytes(Vec<u8>);
+
+impl ser::SerializeSeq for Bytes {
+    type Ok = Param;
+    type Error = Error;
+
+    fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<()> {
+        self.0.push(value.serialize(ByteSerializer)?);
+        Ok(())
+    }
+
+    fn end(self) -> Result<Param> {
+        Ok(Box::new(self.0))
+    }
+}
+
+/// Serializes an element of a sequence of bytes, failing for anything but a
+/// `u8`.
+struct ByteSerializer;
+
+impl ser::Serializer for ByteSerializer {
+    type Ok = u8;
+    type Error = Error;
+    type SerializeSeq = Impossible<u8, Error>;
+    type SerializeTuple = Impossible<u8, Error>;
+    type SerializeTupleStruct = Impossible<u8, Error>;
+    type SerializeTupleVariant = Impossible<u8, Error>;
+    type SerializeMap = Impossible<u8, Error>;
+    type SerializeStruct = Impossible<u8, Error>;
+    type SerializeStructVariant = Impossible<u8, Error>;
+
+    fn serialize_u8(self, v: u8) -> Result<u8> {
+        Ok(v)
+    }
+
+    fn serialize_some<T: ?Sized + Serialize>(self, _: &T) -> Result<u8> {
+        Err(Error::UnsupportedType)
+    }
+
+    fn serialize_newtype_struct<T: ?Sized + Serialize>(self, _: &'static str, _: &T)
+        -> Result<u8>
+    {
+        Err(Error::UnsupportedType)
+    }
+
+    fn serialize_newtype_variant<T: ?Sized + Serialize>(self, _: &'static str, _: u32, _: &'static str, _: &T)
+        -> Result<u8>
+    {
+        Err(Error::UnsupportedType)
+    }
+
+    unsupported! {
+        serialize_bool(bool) -> u8;
+        serialize_i8(i8) -> u8;
+        serialize_i16(i16) -> u8;
+        serialize_i32(i32) -> u8;
+        serialize_i64(i64) -> u8;
+        serialize_u16(u16) -> u8;
+        serialize_u32(u32) -> u8;
+        serialize_u64(u64) -> u8;
+        serialize_f32(f32) -> u8;
+        serialize_f64(f64) -> u8;
+        serialize_char(char) -> u8;
+        serialize_str(&str) -> u8;
+        serialize_bytes(&[u8]) -> u8;
+        serialize_none() -> u8;
+        serialize_unit() -> u8;
+        serialize_unit_struct(&'static str) -> u8;
+        serialize_unit_variant(&'static str, u32, &'static str) -> u8;
+        serialize_seq(Option<usize>) -> Impossible<u8, Error>;
+        serialize_tuple(usize) -> Impossible<u8, Error>;
+        serialize_tuple_struct(&'static str, usize) -> Impossible<u8, Error>;
+        serialize_tuple_variant(&'static str, u32, &'static str, usize) -> Impossible<u8, Error>;
+        serialize_map(Option<usize>) -> Impossible<u8, Error>;
+        serialize_struct(&'static str, usize) -> Impossible<u8, Error>;
+        serialize_struct_variant(&'static str, u32, &'static str, usize) -> Impossible<u8, Error>;
+    }
+}
@@ -0,0 +1,30 @@
+//! "Did you mean" suggestions for field names that match no column.
+
+/// The candidate closest to `target` by edit distance, if any is close
+/// enough to plausibly be a typo of it.
+pub fn closest<'a, I: IntoIterator<Item = &'a str>>(target: &str, candidates: I) -> Option<&'a str> {
+    let max_distance = ::std::cmp::max(1, target.chars().count() / 3);
+    candidates.into_iter()
+        .map(|candidate| (levenshtein(target, candidate), candidate))
+        .filter(|&(distance, _)| distance <= max_distance)
+        .min_by_key(|&(distance, _)| distance)
+        .map(|(_, candidate)| candidate)
+}
+
+/// The number of single character edits needed to turn `a` into `b`.
+fn levenshtein(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    let mut current = vec![0; b.len() + 1];
+
+    for (i, a) in a.chars().enumerate() {
+        current[0] = i + 1;
+        for (j, &b) in b.iter().enumerate() {
+            let substitution = previous[j] + if a == b { 0 } else { 1 };
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        ::std::mem::swap(&mut previous, &mut current);
+    }
+
+    previous[b.len()]
+}
@@ -0,0 +1,29 @@
+//! Conversion of Postgres timestamps into the text form `chrono` reads.
+use chrono::{DateTime, NaiveDate, NaiveDateTime, NaiveTime, Utc};
+use tokio_postgres::types::Type;
+
+use error::Result;
+use raw;
+
+/// Decode a `timestamptz` and format it as RFC 3339 in UTC, which is what
+/// `DateTime<Utc>` deserializes from.
+pub fn to_rfc3339(ty: &Type, raw: Option<&[u8]>) -> Result<String> {
+    raw::decode::<DateTime<Utc>>(ty, raw).map(|timestamp| timestamp.to_rfc3339())
+}
+
+/// Decode a `timestamptz`, `timestamp`, `date` or `time` and format it the
+/// way the matching `chrono` type deserializes from, `None` for any other
+/// type.
+pub fn to_text(ty: &Type, raw: Option<&[u8]>) -> Result<Option<String>> {
+    let text = match *ty {
+        Type::TIMESTAMPTZ => to_rfc3339(ty, raw)?,
+        Type::TIMESTAMP => raw::decode::<NaiveDateTime>(ty, raw)?
+            .format("%Y-%m-%dT%H:%M:%S%.f")
+            .to_string(),
+        Type::DATE => raw::decode::<NaiveDate>(ty, raw)?.to_string(),
+        Type::TIME => raw::decode::<NaiveTime>(ty, raw)?.to_string(),
+        _ => return Ok(None),
+    };
+
+    Ok(Some(text))
+}
@@ -0,0 +1,82 @@
+//! A value deserialized together with the Postgres type it came from.
+use std::fmt;
+use std::marker::PhantomData;
+
+use serde::de::{self, Deserialize, DeserializeSeed, IntoDeserializer, SeqAccess, Visitor};
+
+use error::{Error, Result};
+
+/// Name of the newtype struct `Typed` asks for, so that the `Deserializer`
+/// hands out the column's type along with its value.
+pub const TYPED: &str = "$serde_postgres::Typed";
+
+/// A column's value along with the name of its Postgres type, e.g. to log
+/// which type produced a value when the schema drifts.
+///
+/// Only the row `Deserializer` knows the column's type, so `Typed` can't be
+/// deserialized from anything else.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Typed<T> {
+    /// The column's value.
+    pub value: T,
+    /// The name of the column's Postgres type, e.g. `int4`.
+    pub pg_type: String,
+}
+
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for Typed<T> {
+    fn deserialize<D: de::Deserializer<'de>>(deserializer: D)
+        -> ::std::result::Result<Self, D::Error>
+    {
+        deserializer.deserialize_newtype_struct(TYPED, TypedVisitor(PhantomData))
+    }
+}
+
+struct TypedVisitor<T>(PhantomData<T>);
+
+impl<'de, T: Deserialize<'de>> Visitor<'de> for TypedVisitor<T> {
+    type Value = Typed<T>;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("a column value with its type")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A)
+        -> ::std::result::Result<Self::Value, A::Error>
+    {
+        let pg_type = seq.next_element()?
+            .ok_or_else(|| de::Error::invalid_length(0, &self))?;
+        let value = seq.next_element()?
+            .ok_or_else(|| de::Error::invalid_length(1, &self))?;
+        Ok(Typed { value, pg_type })
+    }
+}
+
+/// Hands out a column's type name and then its value, for `TypedVisitor`.
+pub struct TypedAccess<D> {
+    pg_type: Option<String>,
+    value: Option<D>,
+}
+
+impl<D> TypedAccess<D> {
+    /// Hand out `pg_type`, then the value read from `value`.
+    pub fn new(pg_type: String, value: D) -> Self {
+        TypedAccess { pg_type: Some(pg_type), value: Some(value) }
+    }
+}
+
+impl<'de, D: de::Deserializer<'de, Error = Error>> SeqAccess<'de> for TypedAccess<D> {
+    type Error = Error;
+
+    fn next_element_seed<S: DeserializeSeed<'de>>(&mut self, seed: S)
+        -> Result<Option<S::Value>>
+    {
+        if let Some(pg_type) = self.pg_type.take() {
+            return seed.deserialize(pg_type.into_deserializer()).map(Some);
+        }
+
+        match self.value.take() {
+            Some(value) => seed.deserialize(value).map(Some),
+            None => Ok(None),
+        }
+    }
+}
@@ -0,0 +1,109 @@
+//! Formatting of Postgres geometric types as Well-Known Text.
+use tokio_postgres::types::Type;
+
+use error::{Error, Result};
+
+/// Format a non-`NULL` value of type `ty` as WKT, e.g. `POINT(1 2)`, or
+/// `None` if `ty` has no WKT form.
+///
+/// `lseg` and open `path`s are `LINESTRING`s; `box`, closed `path`s and
+/// `polygon`s are `POLYGON`s. `line` and `circle` aren't supported.
+pub fn from_sql(ty: &Type, raw: &[u8]) -> Result<Option<String>> {
+    let mut buf = raw;
+    let wkt = match *ty {
+        Type::POINT => {
+            let (x, y) = read_point(&mut buf)?;
+            format!("POINT({} {})", x, y)
+        }
+        Type::LSEG => line_string(&read_points(&mut buf, 2)?),
+        Type::BOX => {
+            let (x1, y1) = read_point(&mut buf)?;
+            let (x2, y2) = read_point(&mut buf)?;
+            polygon(&[(x1, y1), (x2, y1), (x2, y2), (x1, y2)])
+        }
+        Type::PATH => {
+            let closed = read_bytes(&mut buf, 1)?[0] != 0;
+            let count = read_count(&mut buf)?;
+            let points = read_points(&mut buf, count)?;
+            if closed { polygon(&points) } else { line_string(&points) }
+        }
+        Type::POLYGON => {
+            let count = read_count(&mut buf)?;
+            polygon(&read_points(&mut buf, count)?)
+        }
+        _ => return Ok(None),
+    };
+
+    if !buf.is_empty() {
+        return Err(invalid(ty));
+    }
+    Ok(Some(wkt))
+}
+
+/// Decode a non-`NULL` `point` into its coordinates.
+pub fn point_from_sql(raw: &[u8]) -> Result<(f64, f64)> {
+    let mut buf = raw;
+    let point = read_point(&mut buf)?;
+    if !buf.is_empty() {
+        return Err(invalid(&Type::POINT));
+    }
+    Ok(point)
+}
+
+fn line_string(points: &[(f64, f64)]) -> String {
+    format!("LINESTRING({})", coordinates(points))
+}
+
+/// A polygon's ring, which WKT closes by repeating the first point.
+fn polygon(points: &[(f64, f64)]) -> String {
+    let mut ring = points.to_vec();
+    if let (Some(&first), Some(&last)) = (ring.first(), ring.last()) {
+        if first != last {
+            ring.push(first);
+        }
+    }
+    format!("POLYGON(({}))", coordinates(&ring))
+}
+
+fn coordinates(points: &[(f64, f64)]) -> String {
+    points.iter()
+        .map(|&(x, y)| format!("{} {}", x, y))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn read_bytes<'a>(buf: &mut &'a [u8], len: usize) -> Result<&'a [u8]> {
+    if buf.len() < len {
+        return Err(Error::InvalidType(String::from("geometric value is truncated")));
+    }
+    let (head, rest) = buf.split_at(len);
+    *buf = rest;
+    Ok(head)
+}
+
+fn read_count(buf: &mut &[u8]) -> Result<usize> {
+    let count = read_bytes(buf, 4)?;
+    let count = i32::from_be_bytes([count[0], count[1], count[2], count[3]]);
+    if count < 0 {
+        return Err(Error::InvalidType(String::from("negative point count")));
+    }
+    Ok(count as usize)
+}
+
+fn read_f64(buf: &mut &[u8]) -> Result<f64> {
+    let mut bits = [0; 8];
+    bits.copy_from_slice(read_bytes(buf, 8)?);
+    Ok(f64::from_bits(u64::from_be_bytes(bits)))
+}
+
+fn read_point(buf: &mut &[u8]) -> Result<(f64, f64)> {
+    Ok((read_f64(buf)?, read_f64(buf)?))
+}
+
+fn read_points(buf: &mut &[u8], count: usize) -> Result<Vec<(f64, f64)>> {
+    (0..count).map(|_| read_point(buf)).collect()
+}
+
+fn invalid(ty: &Type) -> Error {
+    Error::InvalidType(format!("invalid {} value", ty))
+}